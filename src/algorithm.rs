@@ -0,0 +1,345 @@
+use crate::prelude::*;
+
+/// A sequence of moves, such as a scramble or a solution.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Algorithm(Vec<Move>);
+
+impl Algorithm {
+    pub fn new(moves: Vec<Move>) -> Self {
+        Algorithm(moves)
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The algorithm that undoes this one.
+    pub fn invert(&self) -> Algorithm {
+        Algorithm(Move::invert_sequence(&self.0))
+    }
+
+    pub fn concat(&self, other: &Algorithm) -> Algorithm {
+        Algorithm(self.0.iter().chain(&other.0).cloned().collect())
+    }
+
+    pub fn repeat(&self, n: usize) -> Algorithm {
+        Algorithm(self.0.repeat(n))
+    }
+
+    /// Merges consecutive turns of the same face, dropping any that cancel out. Turns of the
+    /// opposite face in between are skipped over, since they commute.
+    pub fn simplify(&self) -> Algorithm {
+        let mut result: Vec<Move> = Vec::with_capacity(self.0.len());
+
+        for &move_ in &self.0 {
+            let same_face = result
+                .iter()
+                .rev()
+                .take_while(|m| Face::same_axis(m.face, move_.face))
+                .position(|m| m.face == move_.face)
+                .map(|from_end| result.len() - 1 - from_end);
+
+            match same_face {
+                None => result.push(move_),
+                Some(i) => match result[i].direction.compose(move_.direction) {
+                    Some(direction) => result[i].direction = direction,
+                    None => {
+                        result.remove(i);
+                    }
+                },
+            }
+        }
+
+        Algorithm(result)
+    }
+
+    /// Length in the half turn metric, where every move counts as one.
+    pub fn len_htm(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Length in the quarter turn metric, where double turns count as two.
+    pub fn len_qtm(&self) -> usize {
+        self.0
+            .iter()
+            .map(|m| match m.direction {
+                Direction::Double => 2,
+                Direction::Single | Direction::Reverse => 1,
+            })
+            .sum()
+    }
+
+    /// Length in the slice turn metric, where opposite faces turning together, like R L', count
+    /// as one slice turn.
+    pub fn len_stm(&self) -> usize {
+        let mut len = 0;
+        let mut moves = self.0.iter().peekable();
+        while let Some(m) = moves.next() {
+            let slice = moves.peek().is_some_and(|next| {
+                next.face == m.face.opposite() && m.direction.compose(next.direction).is_none()
+            });
+            if slice {
+                moves.next();
+            }
+            len += 1;
+        }
+        len
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            htm: self.len_htm(),
+            qtm: self.len_qtm(),
+            stm: self.len_stm(),
+        }
+    }
+}
+
+/// An algorithm's length in each of the common metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metrics {
+    pub htm: usize,
+    pub qtm: usize,
+    pub stm: usize,
+}
+
+impl core::fmt::Display for Metrics {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{} HTM / {} QTM / {} STM", self.htm, self.qtm, self.stm)
+    }
+}
+
+/// Two solutions to the same scramble side by side, e.g. before and after an evaluator change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolutionComparison {
+    pub metrics: [Metrics; 2],
+    pub times: [Duration; 2],
+    /// Whether both leave the cube in the same state, which solutions to one scramble must.
+    pub same_state: bool,
+}
+
+impl SolutionComparison {
+    pub fn new(first: &[Move], second: &[Move], evaluator: &impl Evaluator) -> Self {
+        let solutions = [first, second];
+        SolutionComparison {
+            metrics: solutions.map(|s| Algorithm::new(s.to_vec()).metrics()),
+            times: solutions.map(|s| evaluator.eval(s)),
+            same_state: Cube::solved().apply_all(first.iter().copied())
+                == Cube::solved().apply_all(second.iter().copied()),
+        }
+    }
+}
+
+impl core::fmt::Display for SolutionComparison {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "{} in {:?} vs {} in {:?}",
+            self.metrics[0], self.times[0], self.metrics[1], self.times[1]
+        )?;
+        if !self.same_state {
+            write!(f, ", reaching different states")?;
+        }
+        Ok(())
+    }
+}
+
+/// `moves` in groups of `group_size`, each followed by the running move count, like
+/// `R U R' U' (4) | F R F' (7)`.
+pub fn format_solution(moves: &[Move], group_size: usize) -> String {
+    assert!(group_size > 0, "Groups need at least one move");
+
+    let mut count = 0;
+    moves
+        .chunks(group_size)
+        .map(|group| {
+            count += group.len();
+            format!("{} ({})", Algorithm::new(group.to_vec()), count)
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+impl From<Vec<Move>> for Algorithm {
+    fn from(moves: Vec<Move>) -> Self {
+        Algorithm(moves)
+    }
+}
+
+impl From<Algorithm> for Vec<Move> {
+    fn from(algorithm: Algorithm) -> Self {
+        algorithm.0
+    }
+}
+
+impl IntoIterator for Algorithm {
+    type Item = Move;
+    type IntoIter = std::vec::IntoIter<Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Algorithm {
+    type Item = Move;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, Move>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().copied()
+    }
+}
+
+impl core::str::FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Algorithm> {
+        s.split_whitespace()
+            .map(str::parse)
+            .collect::<anyhow::Result<_>>()
+            .map(Algorithm)
+    }
+}
+
+impl core::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for (i, m) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", m)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alg(s: &str) -> Algorithm {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn formats_groups_with_running_count() {
+        let solution = Move::parse_sequence("R U R' U' F R F' U2 D L2 B' R2").unwrap();
+        assert_eq!(
+            format_solution(&solution, 4),
+            "R U R' U' (4) | F R F' U2 (8) | D L2 B' R2 (12)"
+        );
+        assert_eq!(
+            format_solution(&solution, 5),
+            "R U R' U' F (5) | R F' U2 D L2 (10) | B' R2 (12)"
+        );
+        assert_eq!(format_solution(&[], 4), "");
+    }
+
+    #[quickcheck]
+    fn invert_of_invert(moves: Vec<Move>) -> bool {
+        let algorithm = Algorithm::from(moves);
+        algorithm.invert().invert() == algorithm
+    }
+
+    #[quickcheck]
+    fn concat_inverse_simplifies_to_nothing(moves: Vec<Move>) -> bool {
+        let algorithm = Algorithm::from(moves);
+        algorithm.concat(&algorithm.invert()).simplify().is_empty()
+    }
+
+    #[quickcheck]
+    fn simplify_preserves_cube(moves: Vec<Move>) -> bool {
+        let algorithm = Algorithm::from(moves);
+        Cube::solved().apply_all(&algorithm) == Cube::solved().apply_all(algorithm.simplify())
+    }
+
+    #[quickcheck]
+    fn display_round_trips(moves: Vec<Move>) -> bool {
+        let algorithm = Algorithm::from(moves);
+        algorithm.to_string().parse::<Algorithm>().unwrap() == algorithm
+    }
+
+    #[test]
+    fn simplify_merges_same_face() {
+        assert_eq!(alg("R R").simplify(), alg("R2"));
+        assert_eq!(alg("R2 R2").simplify(), alg(""));
+        assert_eq!(alg("U R R' U").simplify(), alg("U2"));
+    }
+
+    #[test]
+    fn simplify_looks_past_opposite_face() {
+        assert_eq!(alg("R L R'").simplify(), alg("L"));
+        assert_eq!(alg("R U R'").simplify(), alg("R U R'"));
+    }
+
+    #[test]
+    fn repeat_sexy_move() {
+        let sexy = alg("R U R' U'");
+        assert_eq!(sexy.repeat(2), alg("R U R' U' R U R' U'"));
+        assert_eq!(Cube::solved().apply_all(sexy.repeat(6)), Cube::solved());
+    }
+
+    #[test]
+    fn metric_lengths() {
+        let algorithm = alg("R2 U' F B2");
+        assert_eq!(algorithm.len_htm(), 4);
+        assert_eq!(algorithm.len_qtm(), 6);
+    }
+
+    #[test]
+    fn slice_turn_metric() {
+        assert_eq!(alg("R L'").len_stm(), 1);
+        assert_eq!(alg("R2 L2").len_stm(), 1);
+        assert_eq!(alg("R L").len_stm(), 2);
+        assert_eq!(alg("R U L'").len_stm(), 3);
+    }
+
+    #[test]
+    fn sample_scramble_metrics() {
+        let metrics = alg("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2").metrics();
+        assert_eq!(
+            metrics,
+            Metrics {
+                htm: 14,
+                qtm: 22,
+                stm: 11
+            }
+        );
+        assert_eq!(metrics.to_string(), "14 HTM / 22 QTM / 11 STM");
+    }
+
+    #[test]
+    fn compares_equivalent_solutions() {
+        let evaluator = |seq: &[Move]| Duration::from_millis(10) * seq.len() as u32;
+        let sune = alg("R U R' U R U2 R'");
+        let split = alg("R U R' U R U' U' R'");
+        let comparison = SolutionComparison::new(sune.moves(), split.moves(), &evaluator);
+
+        assert!(comparison.same_state);
+        assert_eq!(comparison.metrics[0].htm, 7);
+        assert_eq!(comparison.metrics[1].htm, 8);
+        assert_eq!(comparison.metrics[0].qtm, comparison.metrics[1].qtm);
+        assert_eq!(
+            comparison.times,
+            [Duration::from_millis(70), Duration::from_millis(80)]
+        );
+        assert_eq!(
+            comparison.to_string(),
+            "7 HTM / 8 QTM / 7 STM in 70ms vs 8 HTM / 8 QTM / 8 STM in 80ms"
+        );
+    }
+
+    #[test]
+    fn notices_different_states() {
+        let evaluator = |seq: &[Move]| Duration::from_millis(10) * seq.len() as u32;
+        let comparison = SolutionComparison::new(alg("R").moves(), alg("L").moves(), &evaluator);
+        assert!(!comparison.same_state);
+        assert!(comparison
+            .to_string()
+            .ends_with(", reaching different states"));
+    }
+}
@@ -1,35 +1,190 @@
 use crate::prelude::*;
 
-#[derive(Clone, Copy)]
-pub struct BlastMachineEvaluator;
+/// Move times on the blast machine, which turns every face at once so that a move on the
+/// opposite face to the one before it can ride along for free. Turning the same face again
+/// can't, it has to wait for the first turn to finish.
+///
+/// The default is the machine's own timings, build one with other values for a machine that has
+/// been calibrated differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlastMachineEvaluator {
+    /// A quarter turn, either way.
+    pub single: Duration,
+    /// A half turn.
+    pub double: Duration,
+    /// Whether a move on the opposite face to the one before it takes no time.
+    pub opposite_face_free: bool,
+}
+
+impl Default for BlastMachineEvaluator {
+    fn default() -> Self {
+        BlastMachineEvaluator {
+            single: Duration::from_millis(10),
+            double: Duration::from_millis(14),
+            opposite_face_free: true,
+        }
+    }
+}
+
+impl BlastMachineEvaluator {
+    fn move_time(&self, last_move: Option<Move>, move_: Move) -> Duration {
+        match (last_move, move_) {
+            (Some(last), m) if last.face == m.face => self.turn_time(m),
+            (Some(last), m) if self.opposite_face_free && Face::same_axis(last.face, m.face) => {
+                Duration::default()
+            }
+            (_, m) => self.turn_time(m),
+        }
+    }
+
+    fn turn_time(&self, move_: Move) -> Duration {
+        match move_ {
+            Move {
+                direction: Direction::Double,
+                ..
+            } => self.double,
+            _ => self.single,
+        }
+    }
+}
 
 impl Evaluator for BlastMachineEvaluator {
     fn eval(&self, seq: &[Move]) -> Duration {
-        let single_move_time = Duration::from_millis(10);
-        let double_move_time = Duration::from_millis(14);
-
-        let mut last_move: Option<Move> = None;
-        seq.into_iter()
-            .map(|move_| match (last_move.replace(*move_), move_) {
-                (Some(last), m) if Face::same_axis(last.face, m.face) => Duration::default(),
-
-                (
-                    _,
-                    Move {
-                        direction: Direction::Double,
-                        ..
-                    },
-                ) => double_move_time,
-                (_, _) => single_move_time,
-            })
+        self.eval_from(None, seq)
+    }
+
+    fn eval_from(&self, mut last_move: Option<Move>, seq: &[Move]) -> Duration {
+        seq.iter()
+            .map(|move_| self.move_time(last_move.replace(*move_), *move_))
             .sum()
     }
 
+    fn eval_push(&self, seq: &[Move], seq_time: Duration, next: Move) -> Duration {
+        seq_time + self.move_time(seq.last().copied(), next)
+    }
+
     fn min_time(&self, seq: &[Move]) -> Duration {
-        match seq {
-            [] => Duration::default(),
-            [_] => Duration::default(),
-            [_, internal @ .., _] => self.eval(internal),
+        self.min_time_via_eval(seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck]
+    fn eval_push_matches_eval(moves: Vec<Move>) -> bool {
+        let evaluator = BlastMachineEvaluator::default();
+        let mut time = Duration::default();
+        for i in 0..moves.len() {
+            time = evaluator.eval_push(&moves[..i], time, moves[i]);
         }
+        time == evaluator.eval(&moves)
+    }
+
+    #[quickcheck]
+    fn eval_bounded_matches_eval(moves: Vec<Move>, bound_ms: u8) -> bool {
+        let evaluator = BlastMachineEvaluator::default();
+        let bound = Duration::from_millis(bound_ms as u64);
+        let time = evaluator.eval(&moves);
+        evaluator.eval_bounded(&moves, bound) == Some(time).filter(|&t| t <= bound)
+    }
+
+    #[test]
+    fn eval_bounded_includes_the_bound() {
+        let evaluator = BlastMachineEvaluator::default();
+        let moves = Move::parse_sequence("R U2").unwrap();
+        assert_eq!(
+            evaluator.eval_bounded(&moves, Duration::from_millis(24)),
+            Some(Duration::from_millis(24))
+        );
+        assert_eq!(
+            evaluator.eval_bounded(&moves, Duration::from_millis(23)),
+            None
+        );
+    }
+
+    fn eval(moves: &str) -> Duration {
+        BlastMachineEvaluator::default().eval(&Move::parse_sequence(moves).unwrap())
+    }
+
+    #[test]
+    fn opposite_face_is_free() {
+        assert_eq!(eval("R L"), Duration::from_millis(10));
+        assert_eq!(eval("R L2"), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn same_face_is_not_free() {
+        assert_eq!(eval("R R"), Duration::from_millis(20));
+        assert_eq!(eval("R R'"), Duration::from_millis(20));
+        assert_eq!(eval("R R2"), Duration::from_millis(24));
+    }
+
+    #[test]
+    fn custom_timings() {
+        let evaluator = BlastMachineEvaluator {
+            single: Duration::from_millis(7),
+            double: Duration::from_millis(12),
+            opposite_face_free: false,
+        };
+        let moves = Move::parse_sequence("R L2 U R'").unwrap();
+        assert_eq!(
+            evaluator.eval(&moves),
+            Duration::from_millis(7 + 12 + 7 + 7)
+        );
+        assert_eq!(evaluator.min_time(&moves), Duration::from_millis(12 + 7));
+
+        let free = BlastMachineEvaluator {
+            opposite_face_free: true,
+            ..evaluator
+        };
+        assert_eq!(free.eval(&moves), Duration::from_millis(7 + 7 + 7));
+        assert_eq!(free.min_time(&moves), Duration::from_millis(12 + 7));
+    }
+
+    #[test]
+    fn first_move_can_follow_the_last_one() {
+        let evaluator = BlastMachineEvaluator::default();
+        let r = "R".parse().unwrap();
+        assert_eq!(evaluator.eval(&[r]), Duration::from_millis(10));
+        assert_eq!(
+            evaluator.eval_from(Some("L".parse().unwrap()), &[r]),
+            Duration::default()
+        );
+        assert_eq!(
+            evaluator.eval_from(Some("U".parse().unwrap()), &[r]),
+            Duration::from_millis(10)
+        );
+    }
+
+    #[quickcheck]
+    fn eval_from_matches_eval_of_both(last_move: Move, seq: Vec<Move>) -> bool {
+        let evaluator = BlastMachineEvaluator::default();
+        let both = [&[last_move][..], &seq].concat();
+        evaluator.eval_from(Some(last_move), &seq)
+            == evaluator.eval(&both) - evaluator.eval(&[last_move])
+    }
+
+    #[quickcheck]
+    fn min_time_is_via_eval(seq: Vec<Move>) -> bool {
+        let evaluator = BlastMachineEvaluator::default();
+        evaluator.min_time(&seq) == evaluator.min_time_via_eval(&seq)
+    }
+
+    #[quickcheck]
+    fn is_subadditive(a: Vec<Move>, b: Vec<Move>) -> bool {
+        let evaluator = BlastMachineEvaluator::default();
+        evaluator.eval(&[&a[..], &b].concat()) <= evaluator.eval(&a) + evaluator.eval(&b)
+    }
+
+    #[test]
+    fn min_time_drops_the_ends() {
+        let evaluator = BlastMachineEvaluator::default();
+        let min_time = |seq: Vec<Move>| evaluator.min_time_via_eval(&seq);
+        assert_eq!(min_time(moves![R]), Duration::default());
+        assert_eq!(min_time(moves![R, U, F2, L]), Duration::from_millis(24));
+        // The second of two turns on one axis is free wherever it's played.
+        assert_eq!(min_time(moves![U, R, L, D]), Duration::from_millis(10));
     }
 }
@@ -1,20 +1,138 @@
 use crate::prelude::*;
 
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::path::PathBuf;
+
 pub struct Challenge<E: Evaluator> {
     pub inspection: Duration,
     pub evaluator: E,
+
+    /// Directory to load and save built heuristic tables, so they are only built once.
+    pub table_cache: Option<PathBuf>,
+
+    /// How many moves a threaded `Solver::solve` may compute ahead of its consumer. `None` never
+    /// blocks, `Some(0)` hands over each move only when it is received.
+    pub channel_bound: Option<usize>,
+
+    /// Log a solve's start and finish, the tables it builds and giving up at `debug` rather than
+    /// `info`, so nothing is logged above `debug` while solving.
+    pub quiet: bool,
+}
+
+impl<E: Evaluator> Challenge<E> {
+    /// No inspection, no `table_cache`, an unbounded channel and logging at `info`.
+    pub fn new(evaluator: E) -> Self {
+        Challenge {
+            inspection: Duration::default(),
+            evaluator,
+            table_cache: None,
+            channel_bound: None,
+            quiet: false,
+        }
+    }
+
+    /// The level to log a solve's start and finish at, along with the tables it builds.
+    pub fn log_level(&self) -> log::Level {
+        if self.quiet {
+            log::Level::Debug
+        } else {
+            log::Level::Info
+        }
+    }
 }
 
 // Other code assumes Evaluators are not super-linear.
-//   E(a) + E(b) <= E(a + b)
+//   E(a + b) <= E(a) + E(b)
 pub trait Evaluator: Sync + Send + 'static {
     fn eval(&self, seq: &[Move]) -> Duration;
 
+    /// Time of `seq` when the machine's last move before it was `last_move`, as when the cube
+    /// is loaded straight after being scrambled. `None` is a fresh start, the same as `eval`.
+    ///
+    /// The default ignores what came before.
+    fn eval_from(&self, _last_move: Option<Move>, seq: &[Move]) -> Duration {
+        self.eval(seq)
+    }
+
+    /// Time of `seq`, or `None` if it takes longer than `bound`.
+    ///
+    /// Lets evaluators that sum their moves stop as soon as the sum passes `bound`. The default
+    /// evaluates all of `seq` and compares.
+    fn eval_bounded(&self, seq: &[Move], bound: Duration) -> Option<Duration> {
+        Some(self.eval(seq)).filter(|&time| time <= bound)
+    }
+
     fn min_time(&self, _seq: &[Move]) -> Duration {
         Duration::default()
     }
+
+    /// A `min_time` from `eval` alone, the time of `seq` without its first and last moves.
+    ///
+    /// The ends are the moves that what's played around `seq` can make cheaper, so this relies on
+    /// `eval` being subadditive as assumed above, `E(a + b) <= E(a) + E(b)`, with joining only
+    /// saving time at the seam. It is a lower bound as long as the middle moves cost no more
+    /// without the ends before them.
+    fn min_time_via_eval(&self, seq: &[Move]) -> Duration {
+        match seq {
+            [] | [_] => Duration::default(),
+            [_, internal @ .., _] => self.eval(internal),
+        }
+    }
+
+    /// Time of `seq` followed by `next`, given that `seq` alone takes `seq_time`.
+    ///
+    /// Searches call this as they push moves, keeping each total so popping is free. The
+    /// default re-evaluates the whole sequence.
+    fn eval_push(&self, seq: &[Move], _seq_time: Duration, next: Move) -> Duration {
+        let mut seq = seq.to_vec();
+        seq.push(next);
+        self.eval(&seq)
+    }
+
+    /// Spot check the assumption above on `samples` random pairs of sequences, failing with the
+    /// first pair that takes longer joined than apart.
+    ///
+    /// The pairs are the same on every call, so a failure can be reproduced.
+    fn check_subadditive(&self, samples: usize) -> Result<(), NotSubadditive> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut sequence = || {
+            let len = rng.gen_range(0, 9);
+            (0..len)
+                .map(|_| *Move::ALL.choose(&mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        for _ in 0..samples {
+            let (a, b) = (sequence(), sequence());
+            if self.eval(&[&a[..], &b].concat()) > self.eval(&a) + self.eval(&b) {
+                return Err(NotSubadditive { a, b });
+            }
+        }
+        Ok(())
+    }
 }
 
+/// Two sequences an `Evaluator` takes longer to play one after the other than apart, see
+/// `Evaluator::check_subadditive`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NotSubadditive {
+    pub a: Vec<Move>,
+    pub b: Vec<Move>,
+}
+
+impl core::fmt::Display for NotSubadditive {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "evaluator isn't subadditive, \"{}\" then \"{}\" takes longer than both apart",
+            Algorithm::new(self.a.clone()),
+            Algorithm::new(self.b.clone())
+        )
+    }
+}
+
+impl std::error::Error for NotSubadditive {}
+
 impl<F> Evaluator for F
 where
     F: Send + Sync + 'static + Fn(&[Move]) -> Duration,
@@ -27,3 +145,36 @@ where
         (self)(seq)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+
+    fn squared(seq: &[Move]) -> Duration {
+        Duration::from_millis(1) * (seq.len() * seq.len()) as u32
+    }
+
+    #[test]
+    fn per_move_is_subadditive() {
+        let per_move = |seq: &[Move]| Duration::from_millis(10) * seq.len() as u32;
+        assert_eq!(per_move.check_subadditive(1000), Ok(()));
+    }
+
+    #[test]
+    fn blast_machine_is_subadditive() {
+        assert_eq!(
+            BlastMachineEvaluator::default().check_subadditive(1000),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn super_linear_is_rejected() {
+        let Err(e) = squared.check_subadditive(1000) else {
+            panic!("Accepted a super-linear evaluator");
+        };
+        assert!(!e.a.is_empty() && !e.b.is_empty());
+        assert!(squared(&[&e.a[..], &e.b].concat()) > squared(&e.a) + squared(&e.b));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::prelude::*;
+
+/// How each face is labelled in facelet strings and colored when rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub up: Sticker,
+    pub down: Sticker,
+    pub front: Sticker,
+    pub back: Sticker,
+    pub left: Sticker,
+    pub right: Sticker,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sticker {
+    pub label: char,
+    pub rgb: (u8, u8, u8),
+}
+
+impl ColorScheme {
+    pub fn get(&self, face: Face) -> Sticker {
+        match face {
+            Face::Up => self.up,
+            Face::Down => self.down,
+            Face::Front => self.front,
+            Face::Back => self.back,
+            Face::Left => self.left,
+            Face::Right => self.right,
+        }
+    }
+
+    pub fn face_with_label(&self, label: char) -> Option<Face> {
+        enum_iterator::all::<Face>().find(|&f| self.get(f).label == label)
+    }
+}
+
+/// Face letters as labels, white on top and green in front.
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme {
+            up: Sticker::new('U', (255, 255, 255)),
+            down: Sticker::new('D', (255, 213, 0)),
+            front: Sticker::new('F', (0, 155, 72)),
+            back: Sticker::new('B', (0, 70, 173)),
+            left: Sticker::new('L', (255, 88, 0)),
+            right: Sticker::new('R', (183, 18, 52)),
+        }
+    }
+}
+
+impl Sticker {
+    pub const fn new(label: char, rgb: (u8, u8, u8)) -> Self {
+        Sticker { label, rgb }
+    }
+
+    pub fn hex(&self) -> String {
+        let (r, g, b) = self.rgb;
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// The nearest color in the 256-color palette, from its 6x6x6 cube or its grayscale ramp.
+    pub fn ansi256(&self) -> u8 {
+        const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+        let (r, g, b) = self.rgb;
+        let distance = |(r2, g2, b2): (u8, u8, u8)| {
+            [(r, r2), (g, g2), (b, b2)]
+                .into_iter()
+                .map(|(a, b)| (a as i32 - b as i32).pow(2))
+                .sum::<i32>()
+        };
+
+        let cube = (0..216u8).map(|i| {
+            let rgb = (
+                CUBE_LEVELS[i as usize / 36],
+                CUBE_LEVELS[i as usize / 6 % 6],
+                CUBE_LEVELS[i as usize % 6],
+            );
+            (16 + i, rgb)
+        });
+        let grays = (0..24u8).map(|i| (232 + i, (8 + 10 * i, 8 + 10 * i, 8 + 10 * i)));
+        cube.chain(grays)
+            .min_by_key(|&(_, rgb)| distance(rgb))
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck]
+    fn default_labels_are_face_letters(face: Face) -> bool {
+        ColorScheme::default().get(face).label.to_string() == face.to_string()
+    }
+
+    #[quickcheck]
+    fn label_lookup_round_trips(face: Face) -> bool {
+        let scheme = ColorScheme::default();
+        scheme.face_with_label(scheme.get(face).label) == Some(face)
+    }
+
+    #[test]
+    fn ansi256_of_palette_colors_is_exact() {
+        assert_eq!(Sticker::new('X', (255, 255, 255)).ansi256(), 231);
+        assert_eq!(Sticker::new('X', (255, 0, 0)).ansi256(), 196);
+        assert_eq!(Sticker::new('X', (0, 0, 0)).ansi256(), 16);
+        assert_eq!(Sticker::new('X', (128, 128, 128)).ansi256(), 244);
+    }
+
+    #[test]
+    fn ansi256_is_nearest() {
+        // BOY's blue and orange.
+        assert_eq!(Sticker::new('X', (0, 70, 173)).ansi256(), 25);
+        assert_eq!(Sticker::new('X', (255, 88, 0)).ansi256(), 202);
+    }
+
+    #[test]
+    fn hex_is_zero_padded() {
+        assert_eq!(Sticker::new('X', (0, 70, 173)).hex(), "#0046ad");
+    }
+}
@@ -1,13 +1,13 @@
 use crate::prelude::*;
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::OnceLock;
 
 /// Kociemba-style coordinate cubes.
-
+///
+/// The coordinates fully describe a cube, `to_cube` recovers its stickers.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct CoordCube {
-    pub raw: Cube,
-
     corner_orientation: u16,
     edge_orientation: u16,
 
@@ -22,33 +22,103 @@ impl From<Cube> for CoordCube {
             edge_orientation: edge_orientation(&raw),
             corner_position: corner_position(&raw),
             edge_position: edge_position(&raw),
-
-            raw,
         }
     }
 }
 
+/// How many coordinates a lookup table holds, for estimating its memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableSize {
+    pub name: String,
+    pub states: usize,
+}
+
 impl CoordCube {
+    /// Build the transition tables now rather than on first use, logging progress.
     pub fn init_table() {
-        lazy_static::initialize(&TRANSITION_TABLE);
+        CoordCube::init_table_logging_at(log::Level::Info);
     }
 
-    pub fn apply(mut self, move_: Move) -> Self {
-        self.raw = self.raw.apply(move_);
+    /// Like `init_table`, logging each table's start and finish at `level` rather than `info`.
+    pub fn init_table_logging_at(level: log::Level) {
+        CoordCube::init_table_with_progress(|name, expanded, frontier| {
+            log_progress(level, name, expanded, frontier)
+        });
+    }
 
-        self.corner_orientation = TRANSITION_TABLE
-            .corner_orientation
-            .get(self.corner_orientation, move_);
+    /// Like `init_table`, reporting progress to `progress` instead of the log. It's called
+    /// periodically with the table's name, how many coordinates have been expanded, and how many
+    /// are waiting to be.
+    ///
+    /// Nothing is reported if the tables are already built, or being built on another thread.
+    pub fn init_table_with_progress(progress: impl Fn(&str, usize, usize)) {
+        TRANSITION_TABLE
+            .0
+            .get_or_init(|| TransitionTable::init(&progress));
+    }
+
+    /// The sizes of the transition tables, building them first if needed.
+    pub fn table_sizes() -> Vec<TableSize> {
+        let table = &*TRANSITION_TABLE;
+        [
+            ("corner_orientation", &table.corner_orientation),
+            ("edge_orientation", &table.edge_orientation),
+            ("corner_position", &table.corner_position),
+        ]
+        .into_iter()
+        .map(|(name, single)| TableSize {
+            name: name.to_string(),
+            states: single.states(),
+        })
+        .collect()
+    }
+
+    /// A cube with these corners and every edge solved.
+    pub(super) fn from_corners(corner_orientation: u16, corner_position: u16) -> Self {
+        CoordCube {
+            corner_orientation,
+            edge_orientation: 0,
+            corner_position,
+            edge_position: 0,
+        }
+    }
+
+    pub fn apply(mut self, move_: Move) -> Self {
+        (self.corner_orientation, self.corner_position) =
+            apply_to_corners(self.corner_orientation, self.corner_position, move_);
         self.edge_orientation = TRANSITION_TABLE
             .edge_orientation
             .get(self.edge_orientation, move_);
+
+        self.edge_position = permute_edges(self.edge_position, move_);
+
+        self
+    }
+
+    /// Like `apply` for a domino move on a cube with nothing twisted or flipped. Domino moves
+    /// keep every piece's orientation, so with all of them zero the orientation coordinates
+    /// can't change and aren't looked up. Debug builds check that they really are unchanged.
+    pub fn apply_domino(mut self, move_: Move) -> Self {
+        debug_assert!(super::subgroup::is_domino_move(&move_), "{}", move_);
+        debug_assert_eq!((self.corner_orientation, self.edge_orientation), (0, 0));
+
         self.corner_position = TRANSITION_TABLE
             .corner_position
             .get(self.corner_position, move_);
-        self.edge_position = TRANSITION_TABLE
-            .edge_position
-            .get(self.edge_position, move_);
+        self.edge_position = permute_edges(self.edge_position, move_);
 
+        debug_assert_eq!(
+            self.corner_orientation,
+            TRANSITION_TABLE
+                .corner_orientation
+                .get(self.corner_orientation, move_)
+        );
+        debug_assert_eq!(
+            self.edge_orientation,
+            TRANSITION_TABLE
+                .edge_orientation
+                .get(self.edge_orientation, move_)
+        );
         self
     }
 
@@ -63,25 +133,444 @@ impl CoordCube {
     pub fn corner_position(&self) -> u16 {
         self.corner_position
     }
+
+    pub fn edge_position(&self) -> u32 {
+        self.edge_position
+    }
+
+    /// Pack all coordinates into one integer, for cheap hashing and dedup.
+    ///
+    /// The cube has about 4.3e19 states, more than a `u64` can distinguish.
+    pub fn as_u128(&self) -> u128 {
+        let mut packed = self.corner_orientation as u128;
+        packed = (packed << EDGE_ORIENTATION_BITS) | self.edge_orientation as u128;
+        packed = (packed << CORNER_POSITION_BITS) | self.corner_position as u128;
+        packed = (packed << EDGE_POSITION_BITS) | self.edge_position as u128;
+        packed
+    }
+
+    pub fn from_u128(mut packed: u128) -> Self {
+        let mut take = |bits: u32| {
+            let value = packed & ((1 << bits) - 1);
+            packed >>= bits;
+            value
+        };
+        let edge_position = take(EDGE_POSITION_BITS) as u32;
+        let corner_position = take(CORNER_POSITION_BITS) as u16;
+        let edge_orientation = take(EDGE_ORIENTATION_BITS) as u16;
+        let corner_orientation = take(CORNER_ORIENTATION_BITS) as u16;
+
+        CoordCube {
+            corner_orientation,
+            edge_orientation,
+            corner_position,
+            edge_position,
+        }
+    }
+
+    pub fn corners_solved(&self) -> bool {
+        self.corner_orientation == 0 && self.corner_position == 0
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.corner_orientation == 0
+            && self.edge_orientation == 0
+            && self.corner_position == 0
+            && self.edge_position == 0
+    }
+
+    /// Whether the cube is in the domino group, see [`subgroup::is_domino_cube`](super::subgroup::is_domino_cube).
+    pub fn is_domino(&self) -> bool {
+        self.corner_orientation == 0 && self.edge_orientation == 0 && self.e_slice() == E_SLICE_MASK
+    }
+
+    /// Which edge slots hold the FL, FR, BL and BR cubies, a bit per slot of `EDGE_SLOTS`.
+    pub fn e_slice(&self) -> u16 {
+        let edges = decode_permutation::<12>(self.edge_position as usize);
+        (0..12)
+            .filter(|&slot| E_SLICE.contains(&edges[slot]))
+            .fold(0, |mask, slot| mask | 1 << slot)
+    }
+
+    /// Whether the cube can be solved with half turns alone, Thistlethwaite's G3.
+    pub fn is_half_turn(&self) -> bool {
+        let edges = decode_permutation::<12>(self.edge_position as usize);
+        let in_home_slice = edges.iter().enumerate().all(|(slot, &cubie)| {
+            EDGE_SLOTS[slot].map(Face::axis) == EDGE_SLOTS[cubie as usize].map(Face::axis)
+        });
+
+        self.is_domino()
+            && in_home_slice
+            && is_even_permutation(&edges)
+            && HALF_TURN_CORNERS.contains(&self.corner_position)
+    }
+
+    /// The cube that undoes this one: whatever solves one of them, inverted, solves the other.
+    ///
+    /// Its orientations depend on this cube's positions as well as its orientations, so a table
+    /// over orientation alone can't look the inverse up.
+    pub fn inverse(&self) -> CoordCube {
+        let corners = decode_permutation::<8>(self.corner_position as usize);
+        let twists = corner_twists(self.corner_orientation);
+        let mut inverse_corners = [0; 8];
+        let mut inverse_twists = [0; 8];
+        for (slot, (cubie, twist)) in corners.into_iter().zip(twists).enumerate() {
+            inverse_corners[cubie as usize] = slot as u8;
+            inverse_twists[cubie as usize] = (3 - twist) % 3;
+        }
+
+        let edges = decode_permutation::<12>(self.edge_position as usize);
+        let flips = edge_flips(self.edge_orientation);
+        let mut inverse_edges = [0; 12];
+        let mut inverse_flips = [false; 12];
+        for (slot, (cubie, flipped)) in edges.into_iter().zip(flips).enumerate() {
+            inverse_edges[cubie as usize] = slot as u8;
+            inverse_flips[cubie as usize] = flipped;
+        }
+
+        CoordCube {
+            corner_orientation: encode_twists(inverse_twists),
+            edge_orientation: encode_flips(inverse_flips),
+            corner_position: encode_permutation(&inverse_corners) as u16,
+            edge_position: encode_permutation(&inverse_edges) as u32,
+        }
+    }
+
+    /// Reconstruct the stickers described by these coordinates.
+    pub fn to_cube(&self) -> Cube {
+        let mut cube = Cube::solved();
+
+        let corners = decode_permutation::<8>(self.corner_position as usize);
+        let twists = corner_twists(self.corner_orientation);
+        for (slot, (cubie, twist)) in corners.into_iter().zip(twists).enumerate() {
+            let faces = clockwise(CORNER_SLOTS[slot]);
+            let colors = clockwise(CORNER_SLOTS[cubie as usize]);
+            for (i, color) in colors.into_iter().enumerate() {
+                let face = faces[(twist + i) % 3];
+                let mut others = CORNER_SLOTS[slot].into_iter().filter(|&f| f != face);
+                let location =
+                    Location::Corner(face, others.next().unwrap(), others.next().unwrap());
+                cube.set(location, color);
+            }
+        }
+
+        let edges = decode_permutation::<12>(self.edge_position as usize);
+        let flips = edge_flips(self.edge_orientation);
+        for (slot, (cubie, flipped)) in edges.into_iter().zip(flips).enumerate() {
+            let faces = EDGE_SLOTS[slot];
+            let colors = EDGE_SLOTS[cubie as usize];
+            let face = reference_sticker(faces) ^ flipped as usize;
+            let color = reference_sticker(colors);
+            cube.set(Location::Edge(faces[face], faces[1 - face]), colors[color]);
+            cube.set(
+                Location::Edge(faces[1 - face], faces[face]),
+                colors[1 - color],
+            );
+        }
+
+        cube
+    }
 }
 
-lazy_static::lazy_static! {
-    static ref TRANSITION_TABLE: TransitionTable = TransitionTable::init();
+/// There are too many edge permutations to tabulate, so permute the edges directly.
+fn permute_edges(edge_position: u32, move_: Move) -> u32 {
+    let edges = decode_permutation::<12>(edge_position as usize);
+    let moved = TRANSITION_TABLE.edge_permutation[&move_].map(|from| edges[from as usize]);
+    encode_permutation(&moved) as u32
+}
+
+pub(super) fn apply_to_edge_orientation(edge_orientation: u16, move_: Move) -> u16 {
+    TRANSITION_TABLE
+        .edge_orientation
+        .get(edge_orientation, move_)
+}
+
+/// The `CoordCube::e_slice` mask after `move_`.
+pub(super) fn apply_to_e_slice(e_slice: u16, move_: Move) -> u16 {
+    TRANSITION_TABLE.edge_permutation[&move_]
+        .iter()
+        .enumerate()
+        .filter(|&(_, &from)| e_slice & 1 << from != 0)
+        .fold(0, |mask, (slot, _)| mask | 1 << slot)
+}
+
+/// The corner coordinates after `move_`, without touching any edges.
+pub(super) fn apply_to_corners(
+    corner_orientation: u16,
+    corner_position: u16,
+    move_: Move,
+) -> (u16, u16) {
+    (
+        TRANSITION_TABLE
+            .corner_orientation
+            .get(corner_orientation, move_),
+        TRANSITION_TABLE.corner_position.get(corner_position, move_),
+    )
+}
+
+/// Why a cube can't be solved, see `Cube::check`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CubeError {
+    /// Some color doesn't appear on exactly nine stickers.
+    WrongStickerCount,
+    /// The colors are counted right, but don't make up each cubie exactly once.
+    InvalidCubie,
+    /// The corner twists don't cancel out.
+    CornerTwist,
+    /// An odd number of edges are flipped.
+    EdgeFlip,
+    /// The corner and edge permutations have different parities, as if two cubies were swapped.
+    PermutationParity,
 }
 
-enum Axis {
-    FB,
-    UD,
-    LR,
+impl core::fmt::Display for CubeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let message = match self {
+            CubeError::WrongStickerCount => "a color doesn't have nine stickers",
+            CubeError::InvalidCubie => "cubies are missing or repeated",
+            CubeError::CornerTwist => "a corner is twisted",
+            CubeError::EdgeFlip => "an edge is flipped",
+            CubeError::PermutationParity => "two cubies are swapped",
+        };
+        write!(f, "{}", message)
+    }
 }
 
-impl From<Face> for Axis {
-    fn from(face: Face) -> Self {
-        match face {
-            Face::Up | Face::Down => Axis::UD,
-            Face::Front | Face::Back => Axis::FB,
-            Face::Left | Face::Right => Axis::LR,
+impl std::error::Error for CubeError {}
+
+impl Cube {
+    /// Whether some sequence of moves solves the cube, see `check`.
+    pub fn is_solvable(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// The corners' clockwise twists away from having their U or D sticker on U or D, summed
+    /// mod 3. Zero on every solvable cube.
+    ///
+    /// A corner with no U or D sticker to measure by counts as untwisted, `check` reports it as
+    /// `CubeError::InvalidCubie`.
+    pub fn corner_twist_sum(&self) -> u8 {
+        let twist = CORNER_SLOTS
+            .iter()
+            .filter_map(|&slot| {
+                let faces = clockwise(slot);
+                faces.iter().position(|&face| {
+                    let mut others = slot.into_iter().filter(|&f| f != face);
+                    let location =
+                        Location::Corner(face, others.next().unwrap(), others.next().unwrap());
+                    matches!(self.get(location), Face::Up | Face::Down)
+                })
+            })
+            .sum::<usize>();
+        (twist % 3) as u8
+    }
+
+    /// How many edges are flipped, mod 2. Zero on every solvable cube.
+    pub fn edge_flip_sum(&self) -> u8 {
+        let flips = EDGE_SLOTS
+            .iter()
+            .zip(edge_colors(self))
+            .filter(|&(&faces, colors)| {
+                let face = reference_sticker(faces);
+                let location = Location::Edge(faces[face], faces[1 - face]);
+                self.get(location) != colors[reference_sticker(colors)]
+            })
+            .count();
+        (flips % 2) as u8
+    }
+
+    /// Check the invariants every solvable cube keeps, reporting the first one broken.
+    pub fn check(&self) -> Result<(), CubeError> {
+        for color in enum_iterator::all::<Face>() {
+            if Location::all().filter(|&l| self.get(l) == color).count() != 9 {
+                return Err(CubeError::WrongStickerCount);
+            }
         }
+
+        let mut corners = corner_colors(self);
+        corners.sort();
+        let mut edges = edge_colors(self);
+        edges.sort();
+        if corners != CORNER_SLOTS || edges != EDGE_SLOTS {
+            return Err(CubeError::InvalidCubie);
+        }
+
+        if self.corner_twist_sum() != 0 {
+            return Err(CubeError::CornerTwist);
+        }
+        if self.edge_flip_sum() != 0 {
+            return Err(CubeError::EdgeFlip);
+        }
+
+        if is_even_permutation(&corner_cubies(self)) != is_even_permutation(&edge_cubies(self)) {
+            return Err(CubeError::PermutationParity);
+        }
+        Ok(())
+    }
+}
+
+// 3^7, 2^11, 8! and 12! rounded up to whole bits.
+const CORNER_ORIENTATION_BITS: u32 = 12;
+const EDGE_ORIENTATION_BITS: u32 = 11;
+const CORNER_POSITION_BITS: u32 = 16;
+const EDGE_POSITION_BITS: u32 = 29;
+
+/// Built on first use like the `lazy_static`s, unless `init_table_with_progress` gets there
+/// first to pass its callback.
+struct LazyTable(OnceLock<TransitionTable>);
+
+impl core::ops::Deref for LazyTable {
+    type Target = TransitionTable;
+
+    fn deref(&self) -> &TransitionTable {
+        self.0.get_or_init(|| {
+            TransitionTable::init(&|name, expanded, frontier| {
+                log_progress(log::Level::Info, name, expanded, frontier)
+            })
+        })
+    }
+}
+
+static TRANSITION_TABLE: LazyTable = LazyTable(OnceLock::new());
+
+/// The progress reported when no callback is given, with each table's start and finish at
+/// `level`.
+fn log_progress(level: log::Level, name: &str, expanded: usize, frontier: usize) {
+    match (expanded, frontier) {
+        (0, _) => log::log!(level, "Populating transition table {}", name),
+        (_, 0) => log::log!(
+            level,
+            "Finished populating transition table {}, {} items",
+            name,
+            expanded
+        ),
+        _ => log::debug!("{}: expanded {}, {} to expand", name, expanded, frontier),
+    }
+}
+
+lazy_static::lazy_static! {
+    /// The 96 corner positions reachable with half turns.
+    static ref HALF_TURN_CORNERS: BTreeSet<u16> = {
+        let half_turns = Move::all()
+            .filter(|m| m.direction == Direction::Double)
+            .collect::<Vec<_>>();
+
+        let mut found = BTreeSet::from([0]);
+        let mut to_expand = vec![Cube::solved()];
+        while let Some(cube) = to_expand.pop() {
+            for &m in &half_turns {
+                let next = cube.clone().apply(m);
+                if found.insert(corner_position(&next)) {
+                    to_expand.push(next);
+                }
+            }
+        }
+        found
+    };
+}
+
+/// Corner slots, and the cubies that belong in them, with faces in sorted order.
+const CORNER_SLOTS: [[Face; 3]; 8] = [
+    [Face::Front, Face::Left, Face::Up],
+    [Face::Front, Face::Left, Face::Down],
+    [Face::Front, Face::Right, Face::Up],
+    [Face::Front, Face::Right, Face::Down],
+    [Face::Back, Face::Left, Face::Up],
+    [Face::Back, Face::Left, Face::Down],
+    [Face::Back, Face::Right, Face::Up],
+    [Face::Back, Face::Right, Face::Down],
+];
+
+/// Edge slots, and the cubies that belong in them, with faces in sorted order.
+const EDGE_SLOTS: [[Face; 2]; 12] = [
+    [Face::Front, Face::Left],
+    [Face::Front, Face::Right],
+    [Face::Front, Face::Up],
+    [Face::Front, Face::Down],
+    [Face::Back, Face::Left],
+    [Face::Back, Face::Right],
+    [Face::Back, Face::Up],
+    [Face::Back, Face::Down],
+    [Face::Left, Face::Up],
+    [Face::Left, Face::Down],
+    [Face::Right, Face::Up],
+    [Face::Right, Face::Down],
+];
+
+/// The FL, FR, BL and BR edge slots.
+const E_SLICE: [u8; 4] = [0, 1, 4, 5];
+
+/// `E_SLICE` as a `CoordCube::e_slice` mask.
+pub(super) const E_SLICE_MASK: u16 = 0b11_0011;
+
+/// Faces of a corner in clockwise order, starting from its U/D face.
+///
+/// Going U/D, F/B, L/R is clockwise exactly when an odd number of those faces are D, B or L.
+fn clockwise([fb, lr, ud]: [Face; 3]) -> [Face; 3] {
+    let negative = [fb, lr, ud]
+        .iter()
+        .filter(|f| matches!(f, Face::Down | Face::Back | Face::Left))
+        .count();
+    if negative % 2 == 1 {
+        [ud, fb, lr]
+    } else {
+        [ud, lr, fb]
+    }
+}
+
+/// How many clockwise turns each corner's U/D sticker is from its slot's U/D face.
+fn corner_twists(corner_orientation: u16) -> [usize; 8] {
+    let mut twists = [0; 8];
+    let mut value = corner_orientation as usize;
+    for slot in (0..7).rev() {
+        let [fb, lr, ud] = CORNER_SLOTS[slot];
+        let face = [ud, fb, lr][value % 3];
+        value /= 3;
+        twists[slot] = clockwise(CORNER_SLOTS[slot])
+            .iter()
+            .position(|&f| f == face)
+            .unwrap();
+    }
+    // Twists always sum to a multiple of three, which is why the BRD cubie is skipped.
+    twists[7] = (3 - twists.iter().sum::<usize>() % 3) % 3;
+    twists
+}
+
+/// The inverse of `corner_twists`.
+fn encode_twists(twists: [usize; 8]) -> u16 {
+    (0..7).fold(0, |value, slot| {
+        let [fb, lr, ud] = CORNER_SLOTS[slot];
+        let face = clockwise(CORNER_SLOTS[slot])[twists[slot]];
+        let digit = [ud, fb, lr].iter().position(|&f| f == face).unwrap();
+        value * 3 + digit as u16
+    })
+}
+
+/// The inverse of `edge_flips`.
+fn encode_flips(flips: [bool; 12]) -> u16 {
+    (0..12)
+        .filter(|&slot| slot != 5)
+        .fold(0, |value, slot| value * 2 + flips[slot] as u16)
+}
+
+fn edge_flips(edge_orientation: u16) -> [bool; 12] {
+    let mut flips = [false; 12];
+    let mut value = edge_orientation;
+    for slot in (0..12).rev().filter(|&slot| slot != 5) {
+        flips[slot] = value % 2 == 1;
+        value /= 2;
+    }
+    // An even number of edges are always flipped, which is why the BR edge is skipped.
+    flips[5] = flips.iter().filter(|&&f| f).count() % 2 == 1;
+    flips
+}
+
+/// The index of the sticker that decides an edge's orientation, its U/D face if it has one,
+/// otherwise its F/B face.
+fn reference_sticker([first, second]: [Face; 2]) -> usize {
+    match (first, second) {
+        (_, Face::Up | Face::Down) => 1,
+        _ => 0,
     }
 }
 
@@ -114,36 +603,29 @@ fn corner_orientation(cube: &Cube) -> u16 {
 }
 
 fn corner_position(cube: &Cube) -> u16 {
-    use Face::*;
-    let ordered_cubes = Location::all()
-        .filter_map(|loc| match loc {
-            Location::Corner(major, minor, perp) if major < minor && minor < perp => {
-                Some((major, minor, perp))
-            }
-            _ => None,
-        })
-        .map(|(major, minor, perp)| {
-            let mut faces = [
-                cube.get(Location::Corner(major, minor, perp)),
-                cube.get(Location::Corner(minor, major, perp)),
-                cube.get(Location::Corner(perp, major, minor)),
-            ];
-            faces.sort();
-            (faces[0], faces[1], faces[2])
-        })
-        .map(|cubie| match cubie {
-            (Front, Left, Up) => 0,
-            (Front, Left, Down) => 1,
-            (Front, Right, Up) => 2,
-            (Front, Right, Down) => 3,
-            (Back, Left, Up) => 4,
-            (Back, Left, Down) => 5,
-            (Back, Right, Up) => 6,
-            (Back, Right, Down) => 7,
-            _ => unreachable!(),
-        })
-        .collect::<Vec<_>>();
-    assert_eq!(ordered_cubes.len(), 8);
+    encode_permutation(&corner_cubies(cube)) as u16
+}
+
+/// Which cubie is in each of the `CORNER_SLOTS`.
+fn corner_cubies(cube: &Cube) -> [u8; 8] {
+    corner_colors(cube).map(|faces| CORNER_SLOTS.iter().position(|&c| c == faces).unwrap() as u8)
+}
+
+/// The sorted sticker colors in each of the `CORNER_SLOTS`.
+fn corner_colors(cube: &Cube) -> [[Face; 3]; 8] {
+    CORNER_SLOTS.map(|[major, minor, perp]| {
+        let mut faces = [
+            cube.get(Location::Corner(major, minor, perp)),
+            cube.get(Location::Corner(minor, major, perp)),
+            cube.get(Location::Corner(perp, major, minor)),
+        ];
+        faces.sort();
+        faces
+    })
+}
+
+/// Lehmer code of a permutation, the number of larger values before each value.
+fn encode_permutation(ordered_cubes: &[u8]) -> usize {
     let bad_cubies_before = ordered_cubes
         .iter()
         .enumerate()
@@ -156,16 +638,30 @@ fn corner_position(cube: &Cube) -> u16 {
         });
     bad_cubies_before
         .enumerate()
-        .fold(0, |v, (i, count)| v + factorial(i + 1) * count) as u16
+        .fold(0, |v, (i, count)| v + FACTORIAL[i + 1] * count)
 }
 
-fn factorial(n: usize) -> usize {
-    match n {
-        0 | 1 => 1,
-        n => n * factorial(n - 1),
+fn decode_permutation<const N: usize>(value: usize) -> [u8; N] {
+    let mut remaining = (0..N as u8).collect::<Vec<_>>();
+    let mut ordered_cubes = [0; N];
+    for i in (0..N).rev() {
+        let bad_cubies_before = (value / FACTORIAL[i]) % (i + 1);
+        ordered_cubes[i] = remaining.remove(remaining.len() - 1 - bad_cubies_before);
     }
+    ordered_cubes
 }
 
+/// `n!` for every `n` up to 12, enough for permutations of the 12 edges.
+const FACTORIAL: [usize; 13] = {
+    let mut table = [1; 13];
+    let mut n = 1;
+    while n < table.len() {
+        table[n] = table[n - 1] * n;
+        n += 1;
+    }
+    table
+};
+
 fn edge_orientation(cube: &Cube) -> u16 {
     use Axis::*;
 
@@ -212,52 +708,32 @@ fn edge_orientation(cube: &Cube) -> u16 {
 }
 
 fn edge_position(cube: &Cube) -> u32 {
-    use Face::*;
-    let ordered_cubes = Location::all()
-        .filter_map(|loc| match loc {
-            Location::Edge(major, minor) if major < minor => Some((major, minor)),
-            _ => None,
-        })
-        .map(|(major, minor)| {
-            let mut faces = [
-                cube.get(Location::Edge(major, minor)),
-                cube.get(Location::Edge(minor, major)),
-            ];
-            faces.sort();
-            (faces[0], faces[1])
-        })
-        .map(|cubie| match cubie {
-            (Front, Left) => 0,
-            (Front, Right) => 1,
-            (Front, Up) => 2,
-            (Front, Down) => 3,
-            (Back, Left) => 4,
-            (Back, Right) => 5,
-            (Back, Up) => 6,
-            (Back, Down) => 7,
-            (Left, Up) => 8,
-            (Left, Down) => 9,
-            (Right, Up) => 10,
-            (Right, Down) => 11,
-            _ => unreachable!(),
-        })
-        .collect::<Vec<_>>();
+    encode_permutation(&edge_cubies(cube)) as u32
+}
 
-    assert_eq!(ordered_cubes.len(), 12);
-    let bad_cubies_before = ordered_cubes
-        .iter()
-        .enumerate()
-        .skip(1)
-        .map(|(i, cube_id)| {
-            ordered_cubes[0..i]
-                .iter()
-                .filter(|&other_cube| other_cube > cube_id)
-                .count()
-        });
+/// Which cubie is in each of the `EDGE_SLOTS`.
+fn edge_cubies(cube: &Cube) -> [u8; 12] {
+    edge_colors(cube).map(|faces| EDGE_SLOTS.iter().position(|&e| e == faces).unwrap() as u8)
+}
 
-    bad_cubies_before
-        .enumerate()
-        .fold(0, |v, (i, count)| v + factorial(i + 1) * count) as u32
+/// The sorted sticker colors in each of the `EDGE_SLOTS`.
+fn edge_colors(cube: &Cube) -> [[Face; 2]; 12] {
+    EDGE_SLOTS.map(|[major, minor]| {
+        let mut faces = [
+            cube.get(Location::Edge(major, minor)),
+            cube.get(Location::Edge(minor, major)),
+        ];
+        faces.sort();
+        faces
+    })
+}
+
+fn is_even_permutation(permutation: &[u8]) -> bool {
+    let inversions = (0..permutation.len())
+        .flat_map(|j| (0..j).map(move |i| (i, j)))
+        .filter(|&(i, j)| permutation[i] > permutation[j])
+        .count();
+    inversions % 2 == 0
 }
 
 #[derive(Default)]
@@ -265,25 +741,26 @@ struct TransitionTable {
     corner_orientation: SingleTable<u16>,
     edge_orientation: SingleTable<u16>,
     corner_position: SingleTable<u16>,
-    edge_position: SingleTable<u32>,
+    /// For each move, which slot each edge slot's cubie comes from.
+    edge_permutation: HashMap<Move, [u8; 12]>,
 }
 
 impl TransitionTable {
-    fn init() -> Self {
+    fn init(progress: &dyn Fn(&str, usize, usize)) -> Self {
         let mut table = TransitionTable::default();
 
         table
             .corner_orientation
-            .populate_with("corner_orientation", corner_orientation);
+            .populate_with("corner_orientation", corner_orientation, progress);
         table
             .edge_orientation
-            .populate_with("edge_orientation", edge_orientation);
+            .populate_with("edge_orientation", edge_orientation, progress);
         table
             .corner_position
-            .populate_with("corner_position", corner_position);
-        table
-            .edge_position
-            .populate_with("edge_position", edge_position);
+            .populate_with("corner_position", corner_position, progress);
+        table.edge_permutation = Move::all()
+            .map(|m| (m, edge_cubies(&Cube::solved().apply(m))))
+            .collect();
 
         table
     }
@@ -296,27 +773,33 @@ impl<T> SingleTable<T>
 where
     T: core::hash::Hash + Eq + core::fmt::Debug + Copy + Ord,
 {
-    fn populate_with(&mut self, name: &str, f: impl Fn(&Cube) -> T) {
+    /// Fill the table by expanding every coordinate reachable from solved, reporting to
+    /// `progress` before starting, every 100ms and once more when done.
+    fn populate_with(
+        &mut self,
+        name: &str,
+        f: impl Fn(&Cube) -> T,
+        progress: &dyn Fn(&str, usize, usize),
+    ) {
         use std::time::Instant;
 
-        let start = std::time::Instant::now();
-        log::info!("Populating transition table {}", name);
-
         let mut to_expand = BTreeMap::new();
 
         let solved = Cube::solved();
         to_expand.insert(f(&solved), solved);
+        progress(name, 0, to_expand.len());
 
-        let log_every = Duration::from_millis(100);
-        let mut last_log = Instant::now();
+        let report_every = Duration::from_millis(100);
+        let mut last_report = Instant::now();
+        let mut expanded = 0;
         while let Some((from_v, from)) = pop_front(&mut to_expand) {
             assert!(!self.has_outgoing(&from_v));
 
-            if last_log.elapsed() >= log_every {
-                last_log += log_every;
-                log::info!("to_expand.len(): {}", to_expand.len());
-                log::info!("     self.len(): {}", self.len());
+            if last_report.elapsed() >= report_every {
+                last_report += report_every;
+                progress(name, expanded, to_expand.len());
             }
+            expanded += 1;
 
             for m in Move::all() {
                 let to = from.clone().apply(m);
@@ -332,12 +815,7 @@ where
             }
         }
 
-        log::info!(
-            "Finished populating transition table {}, took {:?}, {} items",
-            name,
-            start.elapsed(),
-            self.len(),
-        );
+        progress(name, expanded, 0);
     }
 
     fn get(&self, from: T, move_: Move) -> T {
@@ -345,10 +823,6 @@ where
     }
 
     fn insert(&mut self, from: T, move_: Move, to: T) {
-        if from == to {
-            return;
-        }
-
         let already = self.0.entry(move_).or_default().insert(from, to);
         assert_eq!(
             already, None,
@@ -357,8 +831,13 @@ where
         );
     }
 
-    fn len(&self) -> usize {
-        self.0.values().map(|v| v.len()).sum()
+    /// How many coordinates have a transition stored, which is every one the moves reach.
+    fn states(&self) -> usize {
+        self.0
+            .values()
+            .flat_map(|map| map.keys())
+            .collect::<BTreeSet<_>>()
+            .len()
     }
 
     fn has_outgoing(&self, t: &T) -> bool {
@@ -375,6 +854,246 @@ fn pop_front<K: Ord + Clone, V>(map: &mut BTreeMap<K, V>) -> Option<(K, V)> {
 mod tests {
     use super::*;
 
+    #[quickcheck]
+    fn inverse_is_inverted_moves(moves: Vec<Move>) -> bool {
+        let cube = CoordCube::from(Cube::solved().apply_all(moves.clone()));
+        let inverted = Cube::solved().apply_all(Move::invert_sequence(&moves));
+        cube.inverse() == CoordCube::from(inverted)
+    }
+
+    #[test]
+    fn inverse_orientation_depends_on_positions() {
+        let [a, b] = ["R", "U R"].map(|s| CoordCube::from(cube_with_moves(s)));
+        assert_eq!(a.corner_orientation(), b.corner_orientation());
+        assert_ne!(
+            a.inverse().corner_orientation(),
+            b.inverse().corner_orientation()
+        );
+    }
+
+    #[quickcheck]
+    fn orientations_encode_round_trip(moves: Vec<Move>) -> bool {
+        let cube = CoordCube::from(Cube::solved().apply_all(moves));
+        encode_twists(corner_twists(cube.corner_orientation)) == cube.corner_orientation
+            && encode_flips(edge_flips(cube.edge_orientation)) == cube.edge_orientation
+    }
+
+    #[quickcheck]
+    fn e_slice_follows_moves(moves: Vec<Move>, m: Move) -> bool {
+        let cube = CoordCube::from(Cube::solved().apply_all(moves));
+        apply_to_e_slice(cube.e_slice(), m) == cube.apply(m).e_slice()
+    }
+
+    #[quickcheck]
+    fn apply_matches_from(moves: Vec<Move>) -> bool {
+        let applied = moves
+            .iter()
+            .fold(CoordCube::from(Cube::solved()), |c, m| c.apply(*m));
+        applied == CoordCube::from(Cube::solved().apply_all(moves))
+    }
+
+    #[quickcheck]
+    fn to_cube_round_trips(moves: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(moves);
+        CoordCube::from(cube.clone()).to_cube() == cube
+    }
+
+    #[quickcheck]
+    fn is_domino_matches_cube(moves: Vec<Move>, domino: Vec<Move>) -> bool {
+        use crate::cube::subgroup::{is_domino_cube, is_domino_move};
+
+        let short = moves.into_iter().take(2);
+        let domino = domino.into_iter().filter(is_domino_move);
+        let cube = Cube::solved().apply_all(short.chain(domino));
+        CoordCube::from(cube.clone()).is_domino() == is_domino_cube(&cube)
+    }
+
+    #[quickcheck]
+    fn apply_domino_matches_apply(moves: Vec<Move>, domino: Vec<Move>) -> bool {
+        use crate::cube::subgroup::is_domino_move;
+
+        let start = CoordCube::from(Cube::solved().apply_all(moves));
+        let start = CoordCube {
+            corner_orientation: 0,
+            edge_orientation: 0,
+            ..start
+        };
+        domino
+            .into_iter()
+            .filter(is_domino_move)
+            .try_fold(start, |cube, m| {
+                let applied = cube.clone().apply(m);
+                (cube.apply_domino(m) == applied).then_some(applied)
+            })
+            .is_some()
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_domino_checks_the_move() {
+        CoordCube::from(Cube::solved()).apply_domino("F".parse().unwrap());
+    }
+
+    #[test]
+    fn populate_reports_progress() {
+        let reports = std::cell::RefCell::new(Vec::new());
+        let mut table = SingleTable::default();
+        table.populate_with(
+            "edge_orientation",
+            edge_orientation,
+            &|name, expanded, frontier| {
+                reports
+                    .borrow_mut()
+                    .push((name.to_string(), expanded, frontier))
+            },
+        );
+
+        let reports = reports.into_inner();
+        assert_eq!(reports[0], ("edge_orientation".to_string(), 0, 1));
+        let (name, expanded, frontier) = reports.last().unwrap();
+        assert_eq!(name, "edge_orientation");
+        assert_eq!((*expanded, *frontier), (2048, 0));
+        assert!(reports.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(table.0.values().all(|map| map.len() == 2048));
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_transitions_panic() {
+        SingleTable::<u16>::default().get(1, "U".parse().unwrap());
+    }
+
+    #[quickcheck]
+    fn u128_round_trips(moves: Vec<Move>) -> bool {
+        let cube = CoordCube::from(Cube::solved().apply_all(moves));
+        CoordCube::from_u128(cube.as_u128()) == cube
+    }
+
+    #[test]
+    fn factorial_table() {
+        fn factorial(n: usize) -> usize {
+            match n {
+                0 | 1 => 1,
+                n => n * factorial(n - 1),
+            }
+        }
+
+        for (n, &value) in FACTORIAL.iter().enumerate() {
+            assert_eq!(value, factorial(n), "{}!", n);
+        }
+    }
+
+    #[test]
+    fn table_sizes() {
+        let sizes = CoordCube::table_sizes()
+            .into_iter()
+            .map(|size| (size.name, size.states))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sizes,
+            [
+                ("corner_orientation".to_string(), 2187),
+                ("edge_orientation".to_string(), 2048),
+                ("corner_position".to_string(), 40320),
+            ]
+        );
+    }
+
+    #[test]
+    fn ninety_six_half_turn_corners() {
+        assert_eq!(HALF_TURN_CORNERS.len(), 96);
+    }
+
+    #[test]
+    fn solved_is_solved() {
+        assert!(CoordCube::from(Cube::solved()).is_solved());
+        assert!(!CoordCube::from(cube_with_moves("U")).is_solved());
+    }
+
+    #[test]
+    fn solved_edge_position_is_zero() {
+        assert_eq!(CoordCube::from(Cube::solved()).edge_position(), 0);
+        assert_ne!(CoordCube::from(cube_with_moves("U")).edge_position(), 0);
+    }
+
+    #[quickcheck]
+    fn arbitrary_cubes_are_solvable(cube: Cube) -> bool {
+        cube.is_solvable()
+    }
+
+    #[test]
+    fn twisted_corner_is_not_solvable() {
+        let mut cube = Cube::solved();
+        cube.set(
+            Location::Corner(Face::Up, Face::Front, Face::Right),
+            Face::Front,
+        );
+        cube.set(
+            Location::Corner(Face::Front, Face::Right, Face::Up),
+            Face::Right,
+        );
+        cube.set(
+            Location::Corner(Face::Right, Face::Front, Face::Up),
+            Face::Up,
+        );
+        assert_eq!(cube.check(), Err(CubeError::CornerTwist));
+        assert!(!cube.is_solvable());
+        assert_eq!(cube.corner_twist_sum(), 1);
+        assert_eq!(cube.edge_flip_sum(), 0);
+    }
+
+    #[test]
+    fn flipped_edge_is_not_solvable() {
+        let mut cube = Cube::solved();
+        cube.set(Location::Edge(Face::Up, Face::Front), Face::Front);
+        cube.set(Location::Edge(Face::Front, Face::Up), Face::Up);
+        assert_eq!(cube.check(), Err(CubeError::EdgeFlip));
+        assert_eq!(cube.edge_flip_sum(), 1);
+        assert_eq!(cube.corner_twist_sum(), 0);
+    }
+
+    #[quickcheck]
+    fn scrambles_have_no_twist_or_flip(moves: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(moves);
+        cube.corner_twist_sum() == 0 && cube.edge_flip_sum() == 0
+    }
+
+    #[test]
+    fn corner_without_up_or_down_has_no_twist() {
+        let mut cube = Cube::solved();
+        cube.set(
+            Location::Corner(Face::Up, Face::Front, Face::Right),
+            Face::Left,
+        );
+        assert_eq!(cube.corner_twist_sum(), 0);
+        assert!(!cube.is_solvable());
+    }
+
+    #[test]
+    fn swapped_edges_are_not_solvable() {
+        let mut cube = Cube::solved();
+        cube.set(Location::Edge(Face::Up, Face::Front), Face::Up);
+        cube.set(Location::Edge(Face::Front, Face::Up), Face::Right);
+        cube.set(Location::Edge(Face::Up, Face::Right), Face::Up);
+        cube.set(Location::Edge(Face::Right, Face::Up), Face::Front);
+        assert_eq!(cube.check(), Err(CubeError::PermutationParity));
+    }
+
+    #[test]
+    fn swapped_stickers_are_not_solvable() {
+        let mut cube = Cube::solved();
+        cube.set(Location::Edge(Face::Up, Face::Front), Face::Right);
+        cube.set(Location::Edge(Face::Right, Face::Up), Face::Up);
+        assert_eq!(cube.check(), Err(CubeError::InvalidCubie));
+    }
+
+    #[test]
+    fn repeated_cubie_is_not_solvable() {
+        let mut cube = Cube::solved();
+        cube.set(Location::Edge(Face::Up, Face::Front), Face::Down);
+        assert_eq!(cube.check(), Err(CubeError::WrongStickerCount));
+    }
+
     #[cfg(test)]
     mod corner_orientation {
         use super::*;
@@ -411,12 +1130,8 @@ mod tests {
         #[test]
         fn double_back_not_double_front() {
             assert_ne!(
-                corner_orientation(
-                    &Cube::solved().apply_all(Move::parse_sequence("R' F2").unwrap())
-                ),
-                corner_orientation(
-                    &Cube::solved().apply_all(Move::parse_sequence("R' B2").unwrap())
-                ),
+                corner_orientation(&Cube::solved().apply_all(moves![Rp, F2])),
+                corner_orientation(&Cube::solved().apply_all(moves![Rp, B2])),
             );
         }
 
@@ -471,11 +1186,7 @@ mod tests {
 
         #[test]
         fn sequence_less_than_2048() {
-            assert!(
-                edge_orientation(
-                    &Cube::solved().apply_all(Move::parse_sequence("D' F B'").unwrap())
-                ) < 2048
-            );
+            assert!(edge_orientation(&Cube::solved().apply_all(moves![Dp, F, Bp])) < 2048);
         }
 
         #[quickcheck]
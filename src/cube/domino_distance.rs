@@ -0,0 +1,210 @@
+use super::coord::{
+    apply_to_corners, apply_to_e_slice, apply_to_edge_orientation, CoordCube, E_SLICE_MASK,
+};
+use crate::prelude::*;
+
+use std::sync::OnceLock;
+
+const CORNER_ORIENTATIONS: usize = 2187;
+const EDGE_ORIENTATIONS: usize = 2048;
+/// The ways to choose four of the twelve edge slots for the E-slice edges.
+const SLICES: usize = 495;
+
+impl CoordCube {
+    /// The fewest moves into the domino group, counting any of the 18 turns as one.
+    ///
+    /// Searched with two breadth-first distance tables, built on first use: orienting the
+    /// corners while placing the E-slice edges, and the same for the edges. Each is a lower
+    /// bound on the whole distance, and the search deepens until one path meets both.
+    pub fn domino_distance(&self) -> usize {
+        let tables = TABLES.get_or_init(Tables::init);
+        let start = Coords {
+            corner: self.corner_orientation(),
+            edge: self.edge_orientation(),
+            slice: tables.slice_index[self.e_slice() as usize],
+        };
+        (0..)
+            .find(|&depth| tables.reaches_within(start, depth, None))
+            .unwrap()
+    }
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+#[derive(Clone, Copy)]
+struct Coords {
+    corner: u16,
+    edge: u16,
+    slice: u16,
+}
+
+struct Tables {
+    /// Each coordinate after each move, indexed by the coordinate and then `Move::index`.
+    corner_moves: Vec<[u16; 18]>,
+    edge_moves: Vec<[u16; 18]>,
+    slice_moves: Vec<[u16; 18]>,
+
+    /// Each `CoordCube::e_slice` mask's index into `slice_moves`.
+    slice_index: Vec<u16>,
+    solved_slice: u16,
+
+    /// Moves to orient the corners and place the E-slice edges, by `corner * SLICES + slice`.
+    corner_distance: Vec<u8>,
+    /// Likewise for the edges.
+    edge_distance: Vec<u8>,
+}
+
+impl Tables {
+    fn init() -> Self {
+        let start = std::time::Instant::now();
+
+        let masks = (0..1 << 12)
+            .filter(|m: &u16| m.count_ones() == 4)
+            .collect::<Vec<_>>();
+        assert_eq!(masks.len(), SLICES);
+        let mut slice_index = vec![0; 1 << 12];
+        for (i, &mask) in masks.iter().enumerate() {
+            slice_index[mask as usize] = i as u16;
+        }
+
+        let corner_moves = transitions(CORNER_ORIENTATIONS, |c, m| apply_to_corners(c, 0, m).0);
+        let edge_moves = transitions(EDGE_ORIENTATIONS, apply_to_edge_orientation);
+        let slice_moves = transitions(SLICES, |s, m| {
+            slice_index[apply_to_e_slice(masks[s as usize], m) as usize]
+        });
+        let solved_slice = slice_index[E_SLICE_MASK as usize];
+
+        let tables = Tables {
+            corner_distance: distances(&corner_moves, &slice_moves, solved_slice),
+            edge_distance: distances(&edge_moves, &slice_moves, solved_slice),
+            corner_moves,
+            edge_moves,
+            slice_moves,
+            slice_index,
+            solved_slice,
+        };
+        log::debug!("Built domino distance tables in {:?}", start.elapsed());
+        tables
+    }
+
+    fn lower_bound(&self, c: Coords) -> usize {
+        let slice = c.slice as usize;
+        let corner = self.corner_distance[c.corner as usize * SLICES + slice];
+        let edge = self.edge_distance[c.edge as usize * SLICES + slice];
+        corner.max(edge) as usize
+    }
+
+    /// Whether some sequence of at most `depth` moves, not starting on the face of `last`,
+    /// takes `c` into the domino group.
+    fn reaches_within(&self, c: Coords, depth: usize, last: Option<Move>) -> bool {
+        let bound = self.lower_bound(c);
+        if bound == 0 {
+            return c.corner == 0 && c.edge == 0 && c.slice == self.solved_slice;
+        }
+        if bound > depth {
+            return false;
+        }
+
+        Move::all()
+            .filter(|m| last.is_none_or(|l| l.face != m.face))
+            .any(|m| {
+                let i = m.index();
+                let next = Coords {
+                    corner: self.corner_moves[c.corner as usize][i],
+                    edge: self.edge_moves[c.edge as usize][i],
+                    slice: self.slice_moves[c.slice as usize][i],
+                };
+                self.reaches_within(next, depth - 1, Some(m))
+            })
+    }
+}
+
+fn transitions(count: usize, apply: impl Fn(u16, Move) -> u16) -> Vec<[u16; 18]> {
+    (0..count as u16)
+        .map(|value| {
+            let mut moved = [0; 18];
+            for m in Move::all() {
+                moved[m.index()] = apply(value, m);
+            }
+            moved
+        })
+        .collect()
+}
+
+/// Breadth-first distances from the solved orientation and slice, by
+/// `orientation * SLICES + slice`.
+fn distances(orientation_moves: &[[u16; 18]], slice_moves: &[[u16; 18]], solved: u16) -> Vec<u8> {
+    let index = |o: u16, s: u16| o as usize * SLICES + s as usize;
+
+    let mut distance = vec![u8::MAX; orientation_moves.len() * SLICES];
+    distance[index(0, solved)] = 0;
+    let mut frontier = vec![(0, solved)];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next = Vec::new();
+        for (o, s) in frontier {
+            for m in 0..18 {
+                let moved = (orientation_moves[o as usize][m], slice_moves[s as usize][m]);
+                let d = &mut distance[index(moved.0, moved.1)];
+                if *d == u8::MAX {
+                    *d = depth;
+                    next.push(moved);
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    debug_assert!(!distance.contains(&u8::MAX));
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cube::subgroup::domino_moves;
+
+    fn distance(moves: &str) -> usize {
+        CoordCube::from(cube_with_moves(moves)).domino_distance()
+    }
+
+    /// The length of the shortest sequence taking `cube` into the domino group, by trying
+    /// every one in order of length.
+    fn brute_force(cube: &Cube) -> usize {
+        (0..)
+            .find(|&len| {
+                Move::canonical_sequences(len)
+                    .any(|seq| CoordCube::from(cube.clone().apply_all(seq)).is_domino())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn domino_cubes_are_zero() {
+        assert_eq!(CoordCube::from(Cube::solved()).domino_distance(), 0);
+        assert_eq!(distance("U R2 D' F2 L2 B2 U2"), 0);
+    }
+
+    #[test]
+    fn quarter_turns_off_the_group_are_one() {
+        for m in Move::all().filter(|m| !domino_moves().any(|d| d == *m)) {
+            assert_eq!(distance(&m.to_string()), 1, "{}", m);
+        }
+    }
+
+    #[test]
+    fn known_distances() {
+        assert_eq!(distance("R F"), 2);
+        assert_eq!(distance("R U F"), 3);
+        assert_eq!(distance("R U F U' R'"), 5);
+        assert_eq!(CoordCube::from(Cube::superflip()).domino_distance(), 10);
+    }
+
+    #[quickcheck]
+    fn matches_brute_force(moves: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(moves.into_iter().take(4));
+        CoordCube::from(cube.clone()).domino_distance() == brute_force(&cube)
+    }
+}
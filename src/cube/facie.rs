@@ -1,6 +1,7 @@
+use super::surface::{sticker_index, try_sticker_index};
 use crate::prelude::*;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Location {
     Center(Face),
     Edge(Face, Face),
@@ -8,6 +9,27 @@ pub enum Location {
 }
 
 impl Location {
+    /// The face this sticker is on.
+    pub fn face(&self) -> Face {
+        match *self {
+            Location::Center(f) | Location::Edge(f, _) | Location::Corner(f, _, _) => f,
+        }
+    }
+
+    /// The sticker on `a` of the edge between `a` and `b`, `None` if they're on one axis.
+    pub fn edge(a: Face, b: Face) -> Option<Location> {
+        let location = Location::Edge(a, b);
+        try_sticker_index(location).map(|_| location)
+    }
+
+    /// The sticker on `a` of the corner between `a`, `b` and `c`, `None` unless they're on three
+    /// different axes. `b` and `c` may come in either order.
+    pub fn corner(a: Face, b: Face, c: Face) -> Option<Location> {
+        [Location::Corner(a, b, c), Location::Corner(a, c, b)]
+            .into_iter()
+            .find(|&l| try_sticker_index(l).is_some())
+    }
+
     pub fn all() -> impl Iterator<Item = Location> {
         let centers = || all_faces();
         let edges = || {
@@ -39,6 +61,44 @@ impl Location {
     }
 }
 
+/// Faces in Speffz lettering order, four letters each.
+const SPEFFZ_FACES: [Face; 6] = [
+    Face::Up,
+    Face::Left,
+    Face::Front,
+    Face::Right,
+    Face::Back,
+    Face::Down,
+];
+
+impl Location {
+    /// The sticker's letter in the Speffz scheme, `None` for centers.
+    ///
+    /// Corners and edges are lettered separately, each face clockwise from its top left as laid
+    /// out in the `Display` net, so every letter names one corner and one edge sticker.
+    pub fn speffz(&self) -> Option<char> {
+        let face = match *self {
+            Location::Center(_) => return None,
+            Location::Edge(s, _) | Location::Corner(s, _, _) => s,
+        };
+        let face_index = SPEFFZ_FACES.iter().position(|&f| f == face).unwrap();
+        let letter = face_index * 4 + sticker_index(*self) / 2;
+        Some((b'A' + letter as u8) as char)
+    }
+
+    pub fn from_speffz_corner(letter: char) -> Option<Location> {
+        Location::all()
+            .filter(|l| matches!(l, Location::Corner(..)))
+            .find(|l| l.speffz() == Some(letter))
+    }
+
+    pub fn from_speffz_edge(letter: char) -> Option<Location> {
+        Location::all()
+            .filter(|l| matches!(l, Location::Edge(..)))
+            .find(|l| l.speffz() == Some(letter))
+    }
+}
+
 fn all_faces() -> impl Iterator<Item = Face> {
     enum_iterator::all()
 }
@@ -67,8 +127,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validating_constructors() {
+        use Face::*;
+
+        assert_eq!(Location::corner(Up, Down, Front), None);
+        assert_eq!(Location::corner(Up, Front, Front), None);
+        assert_eq!(
+            Location::corner(Up, Front, Right),
+            Some(Location::Corner(Up, Front, Right))
+        );
+        assert_eq!(
+            Location::corner(Up, Right, Front),
+            Some(Location::Corner(Up, Front, Right))
+        );
+
+        assert_eq!(Location::edge(Up, Down), None);
+        assert_eq!(Location::edge(Left, Left), None);
+        assert_eq!(Location::edge(Back, Up), Some(Location::Edge(Back, Up)));
+    }
+
+    #[test]
+    fn constructors_accept_every_location() {
+        for location in Location::all() {
+            let built = match location {
+                Location::Center(_) => continue,
+                Location::Edge(a, b) => Location::edge(a, b),
+                Location::Corner(a, b, c) => Location::corner(a, c, b),
+            };
+            assert_eq!(built, Some(location));
+        }
+    }
+
     #[test]
     fn all_locations_is_all() {
         assert_eq!(Location::all().count(), 9 * 6);
     }
+
+    #[test]
+    fn speffz_known_stickers() {
+        use Face::*;
+
+        assert_eq!(Location::Corner(Up, Back, Left).speffz(), Some('A'));
+        assert_eq!(Location::Edge(Up, Back).speffz(), Some('A'));
+        assert_eq!(Location::Corner(Up, Front, Right).speffz(), Some('C'));
+        assert_eq!(Location::Corner(Left, Back, Up).speffz(), Some('E'));
+        assert_eq!(Location::Corner(Front, Left, Up).speffz(), Some('I'));
+        assert_eq!(Location::Edge(Right, Back).speffz(), Some('N'));
+        assert_eq!(Location::Edge(Back, Up).speffz(), Some('Q'));
+        assert_eq!(Location::Corner(Down, Back, Left).speffz(), Some('X'));
+        assert_eq!(Location::Center(Up).speffz(), None);
+    }
+
+    #[test]
+    fn speffz_round_trips() {
+        for location in Location::all() {
+            let from_letter = match location {
+                Location::Center(_) => continue,
+                Location::Edge(..) => Location::from_speffz_edge,
+                Location::Corner(..) => Location::from_speffz_corner,
+            };
+            assert_eq!(from_letter(location.speffz().unwrap()), Some(location));
+        }
+    }
+
+    #[test]
+    fn speffz_uses_24_letters() {
+        let mut corners = Location::all()
+            .filter(|l| matches!(l, Location::Corner(..)))
+            .filter_map(|l| l.speffz())
+            .collect::<Vec<_>>();
+        corners.sort();
+        assert_eq!(corners, ('A'..='X').collect::<Vec<_>>());
+    }
 }
@@ -0,0 +1,338 @@
+use super::rotation::outer_turns;
+use super::stages::in_layer;
+use crate::prelude::*;
+
+/// One of the 57 standard OLL cases, numbered as usual.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct OllCase(u8);
+
+impl OllCase {
+    pub fn number(&self) -> u8 {
+        self.0
+    }
+}
+
+impl core::fmt::Display for OllCase {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "OLL {}", self.0)
+    }
+}
+
+/// One of the 21 standard PLL cases.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, enum_iterator::Sequence)]
+pub enum PllCase {
+    Aa,
+    Ab,
+    E,
+    F,
+    Ga,
+    Gb,
+    Gc,
+    Gd,
+    H,
+    Ja,
+    Jb,
+    Na,
+    Nb,
+    Ra,
+    Rb,
+    T,
+    Ua,
+    Ub,
+    V,
+    Y,
+    Z,
+}
+
+/// An algorithm solving each OLL case, by number.
+const OLL_ALGORITHMS: [&str; 57] = [
+    "R U2 R2 F R F' U2 R' F R F'",
+    "F R U R' U' F' f R U R' U' f'",
+    "f R U R' U' f' U' F R U R' U' F'",
+    "f R U R' U' f' U F R U R' U' F'",
+    "l' U2 L U L' U l",
+    "r U2 R' U' R U' r'",
+    "r U R' U R U2 r'",
+    "l' U' L U' L' U2 l",
+    "R U R' U' R' F R2 U R' U' F'",
+    "R U R' U R' F R F' R U2 R'",
+    "r U R' U R' F R F' R U2 r'",
+    "M' R' U' R U' R' U2 R U' R r'",
+    "F U R U' R2 F' R U R U' R'",
+    "R' F R U R' F' R F U' F'",
+    "l' U' l L' U' L U l' U l",
+    "r U r' R U R' U' r U' r'",
+    "R U R' U R' F R F' U2 R' F R F'",
+    "r U R' U R U2 r2 U' R U' R' U2 r",
+    "r' R U R U R' U' M' R' F R F'",
+    "r U R' U' M2 U R U' R' U' M'",
+    "R U2 R' U' R U R' U' R U' R'",
+    "R U2 R2 U' R2 U' R2 U2 R",
+    "R2 D' R U2 R' D R U2 R",
+    "r U R' U' r' F R F'",
+    "F' r U R' U' r' F R",
+    "R U2 R' U' R U' R'",
+    "R U R' U R U2 R'",
+    "r U R' U' r' R U R U' R'",
+    "R U R' U' R U' R' F' U' F R U R'",
+    "F R' F R2 U' R' U' R U R' F2",
+    "R' U' F U R U' R' F' R",
+    "L U F' U' L' U L F L'",
+    "R U R' U' R' F R F'",
+    "R U R2 U' R' F R U R U' F'",
+    "R U2 R2 F R F' R U2 R'",
+    "L' U' L U' L' U L U L F' L' F",
+    "F R' F' R U R U' R'",
+    "R U R' U R U' R' U' R' F R F'",
+    "L F' L' U' L U F U' L'",
+    "R' F R U R' U' F' U R",
+    "R U R' U R U2 R' F R U R' U' F'",
+    "R' U' R U' R' U2 R F R U R' U' F'",
+    "F' U' L' U L F",
+    "F U R U' R' F'",
+    "F R U R' U' F'",
+    "R' U' R' F R F' U R",
+    "R' U' R' F R F' R' F R F' U R",
+    "F R U R' U' R U R' U' F'",
+    "r U' r2 U r2 U r2 U' r",
+    "r' U r2 U' r2 U' r2 U r'",
+    "F U R U' R' U R U' R' F'",
+    "R U R' U R U' B U' B' R'",
+    "l' U2 L U L' U' L U L' U l",
+    "r U2 R' U' R U R' U' R U' r'",
+    "R' F R U R U' R2 F' R2 U' R' U R U R'",
+    "r' U' r U' R' U R U' R' U R r' U r",
+    "R U R' U' M' U R U' r'",
+];
+
+/// An algorithm solving each PLL case.
+fn pll_algorithm(case: PllCase) -> &'static str {
+    use PllCase::*;
+
+    match case {
+        Aa => "x R' U R' D2 R U' R' D2 R2 x'",
+        Ab => "x R2 D2 R U R' D2 R U' R x'",
+        E => "x' R U' R' D R U R' D' R U R' D R U' R' D' x",
+        F => "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R",
+        Ga => "R2 U R' U R' U' R U' R2 U' D R' U R D'",
+        Gb => "R' U' R U D' R2 U R' U R U' R U' R2 D",
+        Gc => "R2 U' R U' R U R' U R2 U D' R U' R' D",
+        Gd => "R U R' U' D R2 U' R U' R' U R' U R2 D'",
+        H => "M2 U M2 U2 M2 U M2",
+        Ja => "x R2 F R F' R U2 r' U r U2 x'",
+        Jb => "R U R' F' R U R' U' R' F R2 U' R'",
+        Na => "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+        Nb => "R' U R U' R' F' U' F R U R' F R' F' R U' R",
+        Ra => "R U' R' U' R U R D R' U' R D' R' U2 R'",
+        Rb => "R2 F R U R U' R' F' R U2 R' U2 R",
+        T => "R U R' U' R' F R2 U' R' U' R U R' F'",
+        Ua => "M2 U M U2 M' U M2",
+        Ub => "M2 U' M U2 M' U' M2",
+        V => "R' U R' U' y R' F' R2 U' R' U R' F R F",
+        Y => "F R U' R' U' R U R' F' R U R' U' R' F R F'",
+        Z => "M' U M2 U M2 U M' U2 M2",
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Which U layer stickers show the U color in each OLL case, before any U turn.
+    static ref OLL_CASES: Vec<(Vec<bool>, OllCase)> = OLL_ALGORITHMS
+        .iter()
+        .zip(1..)
+        .map(|(algorithm, number)| {
+            let case = Cube::solved().apply_inverse(outer_turns(algorithm).unwrap());
+            (case.orientation_pattern(), OllCase(number))
+        })
+        .collect();
+
+    /// The U layer's colors in each PLL case, for every U turn after the algorithm.
+    static ref PLL_CASES: Vec<(Vec<Face>, PllCase)> = enum_iterator::all::<PllCase>()
+        .flat_map(|case| {
+            let algorithm = outer_turns(pll_algorithm(case)).unwrap();
+            u_turns().map(move |after| {
+                let cube = Cube::solved().apply_all(after).apply_inverse(algorithm.clone());
+                (cube.last_layer_colors(), case)
+            })
+        })
+        .collect();
+}
+
+/// Every way of adjusting the U face, starting with not turning it.
+fn u_turns() -> impl Iterator<Item = Option<Move>> {
+    core::iter::once(None).chain(enum_iterator::all::<Direction>().map(|direction| {
+        Some(Move {
+            face: Face::Up,
+            direction,
+        })
+    }))
+}
+
+impl Cube {
+    /// The OLL case of a cube with its first two layers solved. `None` if they aren't, or if the
+    /// last layer is already oriented.
+    pub fn oll_case(&self) -> Option<OllCase> {
+        if !self.f2l_solved(Face::Down) {
+            return None;
+        }
+        u_turns().find_map(|turn| {
+            let pattern = self.clone().apply_all(turn).orientation_pattern();
+            OLL_CASES
+                .iter()
+                .find(|(p, _)| *p == pattern)
+                .map(|&(_, case)| case)
+        })
+    }
+
+    /// The PLL case of a cube with its first two layers solved and last layer oriented. `None`
+    /// otherwise, or if the last layer only needs a U turn.
+    pub fn pll_case(&self) -> Option<PllCase> {
+        if !self.f2l_solved(Face::Down) || self.oll_case().is_some() {
+            return None;
+        }
+        u_turns().find_map(|turn| {
+            let colors = self.clone().apply_all(turn).last_layer_colors();
+            PLL_CASES
+                .iter()
+                .find(|(c, _)| *c == colors)
+                .map(|&(_, case)| case)
+        })
+    }
+
+    fn orientation_pattern(&self) -> Vec<bool> {
+        Location::all()
+            .filter(|&l| in_layer(l, Face::Up))
+            .map(|l| self.get(l) == Face::Up)
+            .collect()
+    }
+
+    fn last_layer_colors(&self) -> Vec<Face> {
+        Location::all()
+            .filter(|&l| in_layer(l, Face::Up))
+            .map(|l| self.get(l))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    fn setup(algorithm: &str) -> Cube {
+        Cube::solved().apply_inverse(outer_turns(algorithm).unwrap())
+    }
+
+    #[test]
+    fn algorithms_keep_f2l() {
+        let algorithms = OLL_ALGORITHMS
+            .iter()
+            .copied()
+            .chain(enum_iterator::all::<PllCase>().map(pll_algorithm));
+        for algorithm in algorithms {
+            assert!(setup(algorithm).f2l_solved(Face::Down), "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn pll_algorithms_keep_orientation() {
+        for case in enum_iterator::all::<PllCase>() {
+            assert_eq!(setup(pll_algorithm(case)).oll_case(), None, "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn every_oll_case_is_recognized() {
+        for (algorithm, number) in OLL_ALGORITHMS.iter().zip(1..) {
+            for turn in u_turns() {
+                let cube = setup(algorithm).apply_all(turn);
+                assert_eq!(cube.oll_case(), Some(OllCase(number)), "{}", algorithm);
+            }
+        }
+    }
+
+    #[test]
+    fn every_pll_case_is_recognized() {
+        for case in enum_iterator::all::<PllCase>() {
+            for before in u_turns() {
+                for after in u_turns() {
+                    let cube = Cube::solved()
+                        .apply_all(after)
+                        .apply_inverse(outer_turns(pll_algorithm(case)).unwrap())
+                        .apply_all(before);
+                    assert_eq!(cube.pll_case(), Some(case));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cases_are_distinct() {
+        let patterns = |number: usize| {
+            let cube = setup(OLL_ALGORITHMS[number]);
+            u_turns()
+                .map(|turn| cube.clone().apply_all(turn).orientation_pattern())
+                .collect::<HashSet<_>>()
+        };
+        let per_case = (0..57).map(patterns).collect::<Vec<_>>();
+        let all = per_case.iter().flatten().collect::<HashSet<_>>();
+        assert_eq!(all.len(), per_case.iter().map(|p| p.len()).sum::<usize>());
+
+        let pll = PLL_CASES
+            .iter()
+            .map(|(_, case)| case)
+            .collect::<HashSet<_>>();
+        assert_eq!(pll.len(), 21);
+    }
+
+    #[quickcheck]
+    fn every_last_layer_is_recognized(algorithms: Vec<(u8, u8)>) -> bool {
+        let mut cube = Cube::solved();
+        for (algorithm, turn) in algorithms {
+            let algorithm = OLL_ALGORITHMS[algorithm as usize % 57];
+            cube = cube
+                .apply_all(outer_turns(algorithm).unwrap())
+                .apply_all(u_turns().nth(turn as usize % 4).unwrap());
+        }
+
+        let last_layer_solved = u_turns().any(|t| cube.clone().apply_all(t) == Cube::solved());
+        cube.oll_case().is_some() || cube.pll_case().is_some() || last_layer_solved
+    }
+
+    #[test]
+    fn dots_and_crosses() {
+        let top_edges = |number: u8| {
+            let cube = setup(OLL_ALGORITHMS[number as usize - 1]);
+            Face::Up
+                .adjacent()
+                .iter()
+                .filter(|&&side| cube.get(Location::Edge(Face::Up, side)) == Face::Up)
+                .count()
+        };
+        for number in [1, 2, 3, 4, 17, 18, 19, 20] {
+            assert_eq!(top_edges(number), 0, "OLL {}", number);
+        }
+        for number in 21..=27 {
+            assert_eq!(top_edges(number), 4, "OLL {}", number);
+        }
+    }
+
+    #[test]
+    fn known_cases() {
+        // Turning a solved cube sets up the case the inverse algorithm solves.
+        assert_eq!(
+            cube_with_moves("R U2 R' U' R U' R'").oll_case(),
+            Some(OllCase(27))
+        );
+        assert_eq!(
+            cube_with_moves("F U R U' R' F'").oll_case(),
+            Some(OllCase(45))
+        );
+        assert_eq!(
+            cube_with_moves("R U R' U' R' F R2 U' R' U' R U R' F'").pll_case(),
+            Some(PllCase::T)
+        );
+        assert_eq!(cube_with_moves("R").oll_case(), None);
+        assert_eq!(cube_with_moves("U").pll_case(), None);
+    }
+}
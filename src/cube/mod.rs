@@ -1,11 +1,37 @@
 use crate::prelude::*;
 
+mod color;
 pub mod coord;
+mod domino_distance;
 mod facie;
+mod last_layer;
+mod packed;
+mod patterns;
+mod pocket;
+mod reconstruction;
+mod reid;
+mod rotation;
+mod stages;
+pub mod subgroup;
 mod surface;
+mod svg;
 
+pub use color::{ColorScheme, Sticker};
+pub use coord::CubeError;
 pub use facie::Location;
-pub use surface::Cube;
+pub use last_layer::{OllCase, PllCase};
+pub use packed::PackedCube;
+pub use pocket::PocketCube;
+pub use reconstruction::ReconstructionStep;
+pub use rotation::Orientation;
+pub use subgroup::SolveGroup;
+pub use surface::{Colored, Cube};
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The most moves `CubeLike::moves_between` looks for, half from each end.
+pub const MAX_MOVES_BETWEEN: usize = 8;
 
 pub trait CubeLike: Sized + core::fmt::Debug + Eq {
     fn solved() -> Self;
@@ -14,10 +40,86 @@ pub trait CubeLike: Sized + core::fmt::Debug + Eq {
     fn apply_all(self, moves: impl IntoIterator<Item = Move>) -> Self {
         moves.into_iter().fold(self, |cube, m| cube.apply(m))
     }
+
+    fn apply_inverse(self, moves: impl IntoIterator<Item = Move>) -> Self {
+        let moves = moves.into_iter().collect::<Vec<_>>();
+        self.apply_all(Move::invert_sequence(&moves))
+    }
+
+    /// A shortest sequence taking `self` to `other`, or `None` if that takes more than
+    /// `MAX_MOVES_BETWEEN` moves.
+    ///
+    /// Searches breadth first from both ends at once, so holds on the order of 18^4 states per
+    /// side at the limit.
+    fn moves_between(&self, other: &Self) -> Option<Vec<Move>>
+    where
+        Self: Clone + Hash,
+    {
+        if self == other {
+            return Some(Vec::new());
+        }
+
+        let mut forward = Side::new(self);
+        let mut backward = Side::new(other);
+        for depth in 0..MAX_MOVES_BETWEEN {
+            if depth % 2 == 0 {
+                if let Some((there, back)) = forward.expand(&backward) {
+                    return Some(joined(there, back));
+                }
+            } else if let Some((back, there)) = backward.expand(&forward) {
+                return Some(joined(there, back));
+            }
+        }
+        None
+    }
+}
+
+/// One end of a bidirectional search, with the moves reaching each state seen from its start.
+struct Side<C> {
+    seen: HashMap<C, Vec<Move>>,
+    frontier: Vec<C>,
+}
+
+impl<C: CubeLike + Clone + Hash> Side<C> {
+    fn new(start: &C) -> Self {
+        Side {
+            seen: HashMap::from([(start.clone(), Vec::new())]),
+            frontier: vec![start.clone()],
+        }
+    }
+
+    /// Moves the frontier one move further out, stopping at the first state `other` has seen,
+    /// with the moves to it from each start.
+    fn expand(&mut self, other: &Side<C>) -> Option<(Vec<Move>, Vec<Move>)> {
+        for cube in std::mem::take(&mut self.frontier) {
+            for move_ in Move::all() {
+                let next = cube.clone().apply(move_);
+                if self.seen.contains_key(&next) {
+                    continue;
+                }
+
+                let mut moves = self.seen[&cube].clone();
+                moves.push(move_);
+                if let Some(theirs) = other.seen.get(&next) {
+                    return Some((moves, theirs.clone()));
+                }
+                self.seen.insert(next.clone(), moves);
+                self.frontier.push(next);
+            }
+        }
+        None
+    }
+}
+
+/// The moves to a state from the first start, then back from it to the second.
+fn joined(mut there: Vec<Move>, back: Vec<Move>) -> Vec<Move> {
+    there.extend(Move::invert_sequence(&back));
+    there
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, enum_iterator::Sequence)]
 #[cfg_attr(test, derive(Arbitrary))]
+#[repr(u8)]
 pub enum Face {
     Front,
     Back,
@@ -27,21 +129,54 @@ pub enum Face {
     Down,
 }
 
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, enum_iterator::Sequence)]
+pub enum Axis {
+    FB,
+    UD,
+    LR,
+}
+
+impl From<Face> for Axis {
+    fn from(face: Face) -> Self {
+        match face {
+            Face::Up | Face::Down => Axis::UD,
+            Face::Front | Face::Back => Axis::FB,
+            Face::Left | Face::Right => Axis::LR,
+        }
+    }
+}
+
 impl Face {
+    pub fn axis(self) -> Axis {
+        Axis::from(self)
+    }
+
     pub fn same_axis(a: Face, b: Face) -> bool {
-        if a == b {
-            return true;
-        }
+        a.axis() == b.axis()
+    }
 
-        if a > b {
-            return Face::same_axis(b, a);
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::Front => Face::Back,
+            Face::Back => Face::Front,
+            Face::Left => Face::Right,
+            Face::Right => Face::Left,
+            Face::Up => Face::Down,
+            Face::Down => Face::Up,
         }
+    }
+
+    /// The four faces sharing an edge with this one, clockwise when looking at this face.
+    pub fn adjacent(self) -> [Face; 4] {
+        use Face::*;
 
-        match (a, b) {
-            (Face::Front, Face::Back) => true,
-            (Face::Left, Face::Right) => true,
-            (Face::Up, Face::Down) => true,
-            _ => false,
+        match self {
+            Front => [Up, Right, Down, Left],
+            Back => [Up, Left, Down, Right],
+            Left => [Up, Front, Down, Back],
+            Right => [Up, Back, Down, Front],
+            Up => [Back, Right, Front, Left],
+            Down => [Front, Right, Back, Left],
         }
     }
 }
@@ -76,4 +211,74 @@ mod tests {
     fn single_move_is_not_solved() {
         assert_ne!(Cube::solved().apply("F2".parse().unwrap()), Cube::solved());
     }
+
+    #[quickcheck]
+    fn apply_inverse_undoes_apply_all(scramble: Vec<Move>, seq: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(scramble);
+        cube.clone().apply_all(seq.clone()).apply_inverse(seq) == cube
+    }
+
+    #[quickcheck]
+    fn moves_between_reaches_other(a: Vec<Move>, b: Vec<Move>) -> bool {
+        let a = Cube::solved().apply_all(a.into_iter().take(2));
+        let b = Cube::solved().apply_all(b.into_iter().take(2));
+        match a.moves_between(&b) {
+            Some(moves) => moves.len() <= 4 && a.apply_all(moves) == b,
+            None => false,
+        }
+    }
+
+    #[test]
+    fn moves_between_is_shortest() {
+        let a = cube_with_moves("R U");
+        let b = cube_with_moves("R U F' L2 D");
+        assert_eq!(a.moves_between(&b), Some(moves![Fp, L2, D]));
+        assert_eq!(b.moves_between(&b), Some(vec![]));
+    }
+
+    #[test]
+    fn moves_between_pocket_cubes() {
+        let a = PocketCube::solved();
+        let b = PocketCube::solved().apply_all(moves![R, U2, Fp, R]);
+        let moves = a.moves_between(&b).unwrap();
+        assert!(moves.len() <= 4);
+        assert_eq!(a.apply_all(moves), b);
+    }
+
+    #[test]
+    fn moves_between_gives_up_past_limit() {
+        assert_eq!(Cube::solved().moves_between(&Cube::superflip()), None);
+    }
+
+    #[quickcheck]
+    fn opposite_of_opposite(face: Face) -> bool {
+        face.opposite().opposite() == face
+    }
+
+    #[quickcheck]
+    fn opposite_is_same_axis(face: Face) -> bool {
+        face.opposite() != face && Face::same_axis(face, face.opposite())
+    }
+
+    #[quickcheck]
+    fn adjacent_excludes_own_axis(face: Face) -> bool {
+        face.adjacent()
+            .iter()
+            .all(|&a| a != face && a != face.opposite())
+    }
+
+    #[test]
+    fn same_axis_pairs() {
+        assert!(Face::same_axis(Face::Up, Face::Down));
+        assert!(Face::same_axis(Face::Left, Face::Left));
+        assert!(!Face::same_axis(Face::Front, Face::Right));
+    }
+
+    #[test]
+    fn adjacent_is_clockwise() {
+        assert_eq!(
+            Face::Front.adjacent(),
+            [Face::Up, Face::Right, Face::Down, Face::Left]
+        );
+    }
 }
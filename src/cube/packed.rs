@@ -0,0 +1,137 @@
+use crate::prelude::*;
+
+/// A `Cube` packed into one `u64` per face, a byte per sticker clockwise from the top left, so a
+/// move is a rotation of its face and a masked shuffle of the four rows around it.
+///
+/// Applies moves about twice as fast as `Cube`, and converts to and from it for everything else.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PackedCube([u64; 6]);
+
+/// Each face in `Face`'s order, so indexed by a sticker's byte.
+const FACES: [Face; 6] = [
+    Face::Front,
+    Face::Back,
+    Face::Left,
+    Face::Right,
+    Face::Up,
+    Face::Down,
+];
+
+const TOP: u32 = 0;
+const RIGHT: u32 = 2;
+const BOTTOM: u32 = 4;
+const LEFT: u32 = 6;
+
+/// For each face, the rows of stickers its turns cycle, as the face they are on and the index of
+/// their first sticker. A clockwise turn moves each row to the next.
+const CYCLES: [[(Face, u32); 4]; 6] = {
+    use Face::*;
+    [
+        // Front
+        [(Up, BOTTOM), (Right, LEFT), (Down, TOP), (Left, RIGHT)],
+        // Back
+        [(Up, TOP), (Left, LEFT), (Down, BOTTOM), (Right, RIGHT)],
+        // Left
+        [(Up, LEFT), (Front, LEFT), (Down, LEFT), (Back, RIGHT)],
+        // Right
+        [(Up, RIGHT), (Back, LEFT), (Down, RIGHT), (Front, RIGHT)],
+        // Up
+        [(Left, TOP), (Back, TOP), (Right, TOP), (Front, TOP)],
+        // Down
+        [
+            (Left, BOTTOM),
+            (Front, BOTTOM),
+            (Right, BOTTOM),
+            (Back, BOTTOM),
+        ],
+    ]
+};
+
+/// The bits of the three stickers starting at `start`.
+const fn row_mask(start: u32) -> u64 {
+    0xff_ffff_u64.rotate_left(8 * start)
+}
+
+impl CubeLike for PackedCube {
+    fn solved() -> Self {
+        PackedCube::from(&Cube::solved())
+    }
+
+    fn apply(self, move_: Move) -> Self {
+        let quarter_turns = match move_.direction {
+            Direction::Single => 1,
+            Direction::Double => 2,
+            Direction::Reverse => 3,
+        };
+
+        let old = self.0;
+        let mut faces = self.0;
+        let face = move_.face as usize;
+        faces[face] = old[face].rotate_left(16 * quarter_turns);
+
+        let cycle = &CYCLES[face];
+        for (to, &(to_face, to_start)) in cycle.iter().enumerate() {
+            let (from_face, from_start) = cycle[(to + 4 - quarter_turns as usize) % 4];
+            let shift = 8 * ((to_start + 8 - from_start) % 8);
+            let mask = row_mask(to_start);
+
+            let target = &mut faces[to_face as usize];
+            *target = (*target & !mask) | (old[from_face as usize].rotate_left(shift) & mask);
+        }
+
+        PackedCube(faces)
+    }
+}
+
+impl From<&Cube> for PackedCube {
+    fn from(cube: &Cube) -> Self {
+        PackedCube(FACES.map(|face| u64::from_le_bytes(cube.stickers(face).map(|s| s as u8))))
+    }
+}
+
+impl From<PackedCube> for Cube {
+    fn from(packed: PackedCube) -> Self {
+        Cube::from_stickers(|face| {
+            packed.0[face as usize]
+                .to_le_bytes()
+                .map(|byte| FACES[byte as usize])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faces_are_in_declared_order() {
+        assert!(FACES.iter().enumerate().all(|(i, &f)| f as usize == i));
+        assert_eq!(enum_iterator::all::<Face>().collect::<Vec<_>>(), FACES);
+    }
+
+    #[test]
+    fn solved_round_trips() {
+        assert_eq!(Cube::from(PackedCube::solved()), Cube::solved());
+    }
+
+    #[test]
+    fn each_move_matches_cube() {
+        let cube = cube_with_moves("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2");
+        for m in Move::all() {
+            let packed = PackedCube::from(&cube).apply(m);
+            assert_eq!(Cube::from(packed), cube.clone().apply(m), "{}", m);
+        }
+    }
+
+    #[quickcheck]
+    fn apply_all_matches_cube(moves: Vec<Move>) -> bool {
+        let packed = PackedCube::solved().apply_all(moves.clone());
+        Cube::from(packed) == Cube::solved().apply_all(moves)
+    }
+
+    #[quickcheck]
+    fn round_trips(moves: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(moves);
+        Cube::from(PackedCube::from(&cube)) == cube
+    }
+}
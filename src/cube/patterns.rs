@@ -0,0 +1,77 @@
+use crate::prelude::*;
+
+/// Well known patterns, each made by playing its usual algorithm on a solved cube.
+impl Cube {
+    /// Every face a checkerboard of its own color and the opposite one.
+    pub fn checkerboard() -> Cube {
+        Cube::pattern("U2 D2 F2 B2 L2 R2")
+    }
+
+    /// Every edge flipped in place, with everything else solved.
+    pub fn superflip() -> Cube {
+        Cube::pattern("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2")
+    }
+
+    /// A 2x2x2 cube that looks set into the UFR corner, framed on each face by another color.
+    pub fn cube_in_cube() -> Cube {
+        Cube::pattern("F L F U' R U F2 L2 U' L' B D' B' L2 U")
+    }
+
+    fn pattern(algorithm: &str) -> Cube {
+        Cube::solved().apply_str(algorithm).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facelets(cube: Cube) -> String {
+        cube.to_facelets(&ColorScheme::default())
+    }
+
+    #[test]
+    fn patterns_are_solvable_and_not_solved() {
+        for cube in [
+            Cube::checkerboard(),
+            Cube::superflip(),
+            Cube::cube_in_cube(),
+        ] {
+            assert_ne!(cube, Cube::solved());
+            assert!(cube.is_solvable());
+        }
+    }
+
+    #[test]
+    fn checkerboard_facelets() {
+        assert_eq!(
+            facelets(Cube::checkerboard()),
+            "UDUDUDUDURLRLRLRLRFBFBFBFBFDUDUDUDUDLRLRLRLRLBFBFBFBFB"
+        );
+    }
+
+    #[test]
+    fn superflip_facelets() {
+        assert_eq!(
+            facelets(Cube::superflip()),
+            "UBULURUFURURFRBRDRFUFLFRFDFDFDLDRDBDLULBLFLDLBUBRBLBDB"
+        );
+    }
+
+    #[test]
+    fn cube_in_cube_facelets() {
+        // The UFR and DBL 2x2x2 blocks stay solved, and the pieces between them are turned a
+        // third about the UFR-DBL diagonal, carrying F to U, R to F and U to R. So each face keeps
+        // its own color in the 2x2 at its UFR or DBL corner, framed by the color carried onto it.
+        // Rows as in `to_facelets`.
+        let faces = [
+            ["FFF", "FUU", "FUU"], // U, UFR at the bottom right.
+            ["RRU", "RRU", "UUU"], // R, UFR at the top left.
+            ["RFF", "RFF", "RRR"], // F, UFR at the top right.
+            ["BBB", "DDB", "DDB"], // D, DBL at the bottom left.
+            ["DDD", "LLD", "LLD"], // L, DBL at the bottom left.
+            ["LLL", "LBB", "LBB"], // B, DBL at the bottom right.
+        ];
+        assert_eq!(facelets(Cube::cube_in_cube()), faces.concat().concat());
+    }
+}
@@ -0,0 +1,186 @@
+use super::coord::{apply_to_corners, CoordCube};
+use crate::prelude::*;
+
+/// A 2x2x2 cube, tracked as the corners of a 3x3x3 with the same coordinates as `CoordCube`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PocketCube {
+    corner_orientation: u16,
+    corner_position: u16,
+}
+
+impl CubeLike for PocketCube {
+    fn solved() -> Self {
+        PocketCube {
+            corner_orientation: 0,
+            corner_position: 0,
+        }
+    }
+
+    fn apply(self, move_: Move) -> Self {
+        let (corner_orientation, corner_position) =
+            apply_to_corners(self.corner_orientation, self.corner_position, move_);
+        PocketCube {
+            corner_orientation,
+            corner_position,
+        }
+    }
+}
+
+/// The cube's corners, ignoring its edges.
+impl From<Cube> for PocketCube {
+    fn from(cube: Cube) -> Self {
+        let coord = CoordCube::from(cube);
+        PocketCube {
+            corner_orientation: coord.corner_orientation(),
+            corner_position: coord.corner_position(),
+        }
+    }
+}
+
+impl PocketCube {
+    pub fn corner_orientation(&self) -> u16 {
+        self.corner_orientation
+    }
+
+    pub fn corner_position(&self) -> u16 {
+        self.corner_position
+    }
+
+    /// Whether every face is a single colour. With no centres to go by, any whole-cube rotation
+    /// of the solved cube counts.
+    pub fn is_solved(&self) -> bool {
+        let cube = self.to_cube();
+        enum_iterator::all::<Face>().all(|face| {
+            let mut colors = corners().filter(|l| l.face() == face).map(|l| cube.get(l));
+            let first = colors.next();
+            colors.all(|c| Some(c) == first)
+        })
+    }
+
+    /// Whether the DBL corner is home and untwisted. R, U and F turns never move it, so
+    /// otherwise they can't solve the cube.
+    pub fn back_down_left_solved(&self) -> bool {
+        use Face::*;
+
+        let cube = self.to_cube();
+        [
+            Location::Corner(Down, Back, Left),
+            Location::Corner(Back, Left, Down),
+            Location::Corner(Left, Back, Down),
+        ]
+        .into_iter()
+        .all(|l| cube.get(l) == l.face())
+    }
+
+    /// The cube with its colours swapped so the DBL corner is home and untwisted.
+    ///
+    /// Swapping colours commutes with turns, so moves solving the result take this cube to a
+    /// whole-cube rotation of solved, which `is_solved` counts.
+    pub fn with_back_down_left_home(&self) -> PocketCube {
+        use Face::*;
+
+        let cube = self.to_cube();
+        let home = [Down, Back, Left];
+        let colors = [
+            Location::Corner(Down, Back, Left),
+            Location::Corner(Back, Left, Down),
+            Location::Corner(Left, Back, Down),
+        ]
+        .map(|l| cube.get(l));
+        let recolor = |color: Face| {
+            home.into_iter()
+                .zip(colors)
+                .find_map(|(to, from)| {
+                    if color == from {
+                        Some(to)
+                    } else if color == from.opposite() {
+                        Some(to.opposite())
+                    } else {
+                        None
+                    }
+                })
+                .expect("a corner's stickers are on three axes")
+        };
+
+        let mut recolored = Cube::solved();
+        for l in corners() {
+            recolored.set(l, recolor(cube.get(l)));
+        }
+        PocketCube::from(recolored)
+    }
+
+    /// A 3x3x3 with these corners and its edges solved.
+    pub fn to_cube(&self) -> Cube {
+        CoordCube::from_corners(self.corner_orientation, self.corner_position).to_cube()
+    }
+}
+
+fn corners() -> impl Iterator<Item = Location> {
+    Location::all().filter(|l| matches!(l, Location::Corner(..)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[quickcheck]
+    fn apply_matches_cube(moves: Vec<Move>) -> bool {
+        let pocket = PocketCube::solved().apply_all(moves.clone());
+        pocket == PocketCube::from(Cube::solved().apply_all(moves))
+    }
+
+    #[quickcheck]
+    fn to_cube_round_trips(moves: Vec<Move>) -> bool {
+        let pocket = PocketCube::solved().apply_all(moves);
+        PocketCube::from(pocket.to_cube()) == pocket
+    }
+
+    #[quickcheck]
+    fn r_u_f_keep_back_down_left(moves: Vec<Move>) -> bool {
+        let moves = moves
+            .into_iter()
+            .filter(|m| matches!(m.face, Face::Right | Face::Up | Face::Front));
+        PocketCube::solved()
+            .apply_all(moves)
+            .back_down_left_solved()
+    }
+
+    #[test]
+    fn other_faces_move_back_down_left() {
+        for m in ["L", "D'", "B2"] {
+            let pocket = PocketCube::from(cube_with_moves(m));
+            assert!(!pocket.back_down_left_solved(), "{}", m);
+        }
+    }
+
+    #[test]
+    fn rotations_of_solved_are_solved() {
+        // Each of these turns the whole 2x2.
+        for m in ["R L'", "U D'", "F B'", "R2 L2"] {
+            let pocket = PocketCube::from(cube_with_moves(m));
+            assert_ne!(pocket, PocketCube::solved());
+            assert!(pocket.is_solved(), "{}", m);
+        }
+    }
+
+    #[quickcheck]
+    fn back_down_left_home_after_recoloring(moves: Vec<Move>) -> bool {
+        let pocket = PocketCube::solved().apply_all(moves);
+        pocket.with_back_down_left_home().back_down_left_solved()
+    }
+
+    #[quickcheck]
+    fn recoloring_commutes_with_turns(scramble: Vec<Move>, moves: Vec<Move>) -> bool {
+        let pocket = PocketCube::solved().apply_all(scramble);
+        let recolored = pocket.with_back_down_left_home().apply_all(moves.clone());
+        recolored.is_solved() == pocket.apply_all(moves).is_solved()
+    }
+
+    #[test]
+    fn edges_are_ignored() {
+        // Swaps two pairs of edges, leaving the corners alone.
+        let cube = cube_with_moves("R2 U2 R2 U2 R2 U2");
+        assert_ne!(cube, Cube::solved());
+        assert!(PocketCube::from(cube).is_solved());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::prelude::*;
+
+/// One frame of a solve, the move just made and the facelets it left.
+///
+/// With the `serde` feature, serializes as `{"move":"R'","state":"UUU…"}`, with a `null` move for
+/// the starting state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ReconstructionStep {
+    /// `None` for the starting state.
+    #[cfg_attr(feature = "serde", serde(rename = "move"))]
+    pub move_: Option<Move>,
+    /// See `Cube::to_facelets`, with the default color scheme.
+    pub state: String,
+}
+
+impl Cube {
+    /// Every state passed through applying `moves`, starting with this cube.
+    pub fn reconstruction(&self, moves: &[Move]) -> Vec<ReconstructionStep> {
+        let scheme = ColorScheme::default();
+
+        let initial = ReconstructionStep {
+            move_: None,
+            state: self.to_facelets(&scheme),
+        };
+        let states = self.clone().scan(moves.iter().copied());
+        let steps = moves
+            .iter()
+            .zip(states)
+            .map(|(&m, cube)| ReconstructionStep {
+                move_: Some(m),
+                state: cube.to_facelets(&scheme),
+            });
+        core::iter::once(initial).chain(steps).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_initial_state() {
+        let cube = cube_with_moves("R");
+        let steps = cube.reconstruction(&["R'".parse().unwrap()]);
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].move_, None);
+        assert_eq!(steps[0].state, cube.to_facelets(&ColorScheme::default()));
+        assert_eq!(steps[1].move_, Some("R'".parse().unwrap()));
+        assert_eq!(
+            steps[1].state,
+            Cube::solved().to_facelets(&ColorScheme::default())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serializes_as_json() {
+        let steps = cube_with_moves("R").reconstruction(&["R'".parse().unwrap()]);
+        assert_eq!(
+            serde_json::to_string(&steps).unwrap(),
+            concat!(
+                r#"[{"move":null,"state":"UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"},"#,
+                r#"{"move":"R'","state":"UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"}]"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use super::rotation::outer_turns;
+use crate::prelude::*;
+
+use core::iter::Peekable;
+use core::str::Chars;
+
+/// The most times a group may repeat. No real algorithm comes close.
+const MAX_REPEATS: u32 = 100;
+/// The most moves groups may expand to, so nesting them can't multiply past it either.
+const MAX_EXPANDED: usize = 10_000;
+
+impl Move {
+    /// Outer turns for an algorithm as cubing.js writes it.
+    ///
+    /// Along with everything `outer_turns` takes, this accepts wide turns written `Rw`, any
+    /// amount with an optional prime (`R3`, `U2'`), groups with a repeat count (`(R U)2`),
+    /// commutators (`[R, U]`), conjugates (`[F: R U]`), pauses (`.`) and `//` comments. Moves
+    /// needn't be separated by whitespace. Groups repeating more than 100 times are rejected.
+    ///
+    /// ```
+    /// use blastcube::prelude::*;
+    ///
+    /// assert_eq!(
+    ///     Move::parse_reid("[F: [R, U]]").unwrap(),
+    ///     Move::parse_sequence("F R U R' U' F'").unwrap()
+    /// );
+    /// ```
+    pub fn parse_reid(s: &str) -> anyhow::Result<Vec<Move>> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+        };
+        let tokens = parser.sequence()?;
+        if let Some(c) = parser.chars.next() {
+            anyhow::bail!("Unexpected {}", c);
+        }
+
+        let notation = tokens
+            .iter()
+            .filter_map(Token::notation)
+            .collect::<Vec<_>>();
+        outer_turns(&notation.join(" "))
+    }
+
+    /// `seq` as cubing.js writes it, which `parse_reid` reads back unchanged.
+    pub fn to_reid(seq: &[Move]) -> String {
+        Algorithm::new(seq.to_vec()).to_string()
+    }
+}
+
+/// A single turn of the family `letter`, as `outer_turns` names them.
+#[derive(Clone, Copy)]
+struct Token {
+    letter: char,
+    quarter_turns: u8,
+}
+
+impl Token {
+    fn inverse(self) -> Token {
+        Token {
+            quarter_turns: (4 - self.quarter_turns) % 4,
+            ..self
+        }
+    }
+
+    fn notation(&self) -> Option<String> {
+        let suffix = match self.quarter_turns {
+            0 => return None,
+            1 => "",
+            2 => "2",
+            _ => "'",
+        };
+        Some(format!("{}{}", self.letter, suffix))
+    }
+}
+
+fn invert(tokens: &[Token]) -> Vec<Token> {
+    tokens.iter().rev().map(|t| t.inverse()).collect()
+}
+
+struct Parser<'s> {
+    chars: Peekable<Chars<'s>>,
+}
+
+impl Parser<'_> {
+    /// Units up to the end of the input or a closing bracket or separator, which is left for
+    /// the caller.
+    fn sequence(&mut self) -> anyhow::Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_blank();
+            match self.chars.peek() {
+                None | Some(')' | ']' | ',' | ':') => return Ok(tokens),
+                Some('.') => {
+                    self.chars.next();
+                }
+                Some('(') => {
+                    self.chars.next();
+                    let group = self.sequence()?;
+                    self.expect(')')?;
+                    tokens.extend(self.repeated(&group)?);
+                }
+                Some('[') => {
+                    self.chars.next();
+                    let a = self.sequence()?;
+                    let separator = self.chars.next();
+                    let b = self.sequence()?;
+                    self.expect(']')?;
+                    let bracket = match separator {
+                        Some(',') => [&a[..], &b, &invert(&a), &invert(&b)].concat(),
+                        Some(':') => [&a[..], &b, &invert(&a)].concat(),
+                        _ => anyhow::bail!("Expected , or : in brackets"),
+                    };
+                    tokens.extend(self.repeated(&bracket)?);
+                }
+                Some(_) => tokens.push(self.token()?),
+            }
+        }
+    }
+
+    fn token(&mut self) -> anyhow::Result<Token> {
+        let mut letter = self.chars.next().unwrap();
+        if !"FBLRUDfblrudMESxyz".contains(letter) {
+            anyhow::bail!("Unrecognized face {}", letter);
+        }
+        if letter.is_ascii_uppercase() && self.chars.next_if_eq(&'w').is_some() {
+            letter = letter.to_ascii_lowercase();
+        }
+
+        let (amount, prime) = self.amount()?;
+        let quarter_turns = (amount % 4) as u8;
+        let token = Token {
+            letter,
+            quarter_turns,
+        };
+        Ok(if prime { token.inverse() } else { token })
+    }
+
+    /// `tokens` as many times as the amount following them, backwards when it's primed.
+    fn repeated(&mut self, tokens: &[Token]) -> anyhow::Result<Vec<Token>> {
+        let (amount, prime) = self.amount()?;
+        if amount > MAX_REPEATS {
+            anyhow::bail!("Repeat count {} is more than {}", amount, MAX_REPEATS);
+        }
+        if tokens.len() * amount as usize > MAX_EXPANDED {
+            anyhow::bail!("Group expands to more than {} moves", MAX_EXPANDED);
+        }
+        let once = if prime {
+            invert(tokens)
+        } else {
+            tokens.to_vec()
+        };
+        Ok(once.repeat(amount as usize))
+    }
+
+    /// The repeat count after a move or group, 1 when absent, and whether it's primed.
+    fn amount(&mut self) -> anyhow::Result<(u32, bool)> {
+        let mut digits = String::new();
+        while let Some(d) = self.chars.next_if(char::is_ascii_digit) {
+            digits.push(d);
+        }
+        let amount = if digits.is_empty() {
+            1
+        } else {
+            digits.parse()?
+        };
+        let prime = self.chars.next_if_eq(&'\'').is_some();
+        Ok((amount, prime))
+    }
+
+    fn skip_blank(&mut self) {
+        loop {
+            while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            let mut ahead = self.chars.clone();
+            if ahead.next() != Some('/') || ahead.next() != Some('/') {
+                return;
+            }
+            while self.chars.next_if(|&c| c != '\n').is_some() {}
+        }
+    }
+
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        match self.chars.next() {
+            Some(found) if found == c => Ok(()),
+            Some(found) => anyhow::bail!("Expected {}, found {}", c, found),
+            None => anyhow::bail!("Expected {}", c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Vec<Move> {
+        Move::parse_reid(s).unwrap()
+    }
+
+    #[test]
+    fn plain_moves() {
+        let t_perm = "R U R' U' R' F R2 U' R' U' R U R' F'";
+        assert_eq!(parse(t_perm), Move::parse_sequence(t_perm).unwrap());
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse("R3 U2' F4 L1"), moves![Rp, U2, L]);
+        assert_eq!(parse("R U2' R'"), parse("R U2 R'"));
+    }
+
+    #[test]
+    fn wide_turns_either_way() {
+        assert_eq!(parse("Rw U Rw' U'"), parse("r U r' U'"));
+        assert_eq!(parse("r U r' U'"), outer_turns("r U r' U'").unwrap());
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(parse("[R, U]"), moves![R, U, Rp, Up]);
+        assert_eq!(parse("[F: [R, U]]"), moves![F, R, U, Rp, Up, Fp]);
+        assert_eq!(parse("[R, U]'"), moves![U, R, Up, Rp]);
+    }
+
+    #[test]
+    fn groups_repeat() {
+        assert_eq!(parse("(R U)2"), moves![R, U, R, U]);
+        assert_eq!(parse("(R U)'"), moves![Up, Rp]);
+        assert!(Cube::solved().apply_all(parse("(R U R' U')6")) == Cube::solved());
+    }
+
+    #[test]
+    fn comments_and_pauses() {
+        let alg = "R U R' // insert\n. U' (F R)  // done";
+        assert_eq!(parse(alg), moves![R, U, Rp, Up, F, R]);
+    }
+
+    #[test]
+    fn without_spaces() {
+        assert_eq!(parse("RUR'U'"), moves![R, U, Rp, Up]);
+    }
+
+    #[test]
+    fn a_perm_with_rotations() {
+        let a_perm = Cube::solved().apply_all(parse("x R' U R' D2 R U' R' D2 R2 x'"));
+        assert!(a_perm != Cube::solved());
+        assert!(a_perm.f2l_solved(Face::Down));
+        assert!(a_perm.last_layer_oriented(Face::Down));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(Move::parse_reid("R Q").is_err());
+        assert!(Move::parse_reid("(R U").is_err());
+        assert!(Move::parse_reid("[R U]").is_err());
+        assert!(Move::parse_reid("R U)").is_err());
+    }
+
+    #[test]
+    fn rejects_huge_repeats() {
+        assert!(Move::parse_reid("(R U)4000000000").is_err());
+        assert!(Move::parse_reid("(R U)101").is_err());
+        assert!(Move::parse_reid("((((R U)100)100)100)100").is_err());
+        assert_eq!(parse("(R U)100").len(), 200);
+    }
+
+    #[quickcheck]
+    fn to_reid_round_trips(moves: Vec<Move>) -> bool {
+        parse(&Move::to_reid(&moves)) == moves
+    }
+
+    #[test]
+    fn to_reid_is_stable() {
+        let once = Move::to_reid(&parse("[F: [R, U]] (Rw U)2' M2"));
+        assert_eq!(Move::to_reid(&parse(&once)), once);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::prelude::*;
+
+/// A whole-cube rotation, as the face each face is carried to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Rotation([Face; 6]);
+
+impl Rotation {
+    const IDENTITY: Rotation = Rotation([
+        Face::Front,
+        Face::Back,
+        Face::Left,
+        Face::Right,
+        Face::Up,
+        Face::Down,
+    ]);
+
+    /// Rotating the whole cube like an R turn.
+    const X: Rotation = Rotation([
+        Face::Up,
+        Face::Down,
+        Face::Left,
+        Face::Right,
+        Face::Back,
+        Face::Front,
+    ]);
+
+    /// Rotating the whole cube like a U turn.
+    const Y: Rotation = Rotation([
+        Face::Left,
+        Face::Right,
+        Face::Back,
+        Face::Front,
+        Face::Up,
+        Face::Down,
+    ]);
+
+    /// Rotating the whole cube like an F turn.
+    const Z: Rotation = Rotation([
+        Face::Front,
+        Face::Back,
+        Face::Up,
+        Face::Down,
+        Face::Right,
+        Face::Left,
+    ]);
+
+    fn get(&self, face: Face) -> Face {
+        self.0[face as usize]
+    }
+
+    fn then(&self, other: Rotation) -> Rotation {
+        Rotation(self.0.map(|f| other.get(f)))
+    }
+
+    fn inverse(&self) -> Rotation {
+        self.then(*self).then(*self)
+    }
+
+    fn location(&self, location: Location) -> Location {
+        match location {
+            Location::Center(f) => Location::Center(self.get(f)),
+            Location::Edge(s, o) => Location::Edge(self.get(s), self.get(o)),
+            Location::Corner(s, a, b) => {
+                let (a, b) = (self.get(a), self.get(b));
+                Location::Corner(self.get(s), a.min(b), a.max(b))
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Every rotation generated by `X` and `Y`.
+    static ref ROTATIONS: Vec<Rotation> = {
+        let mut rotations = vec![Rotation::IDENTITY];
+        let mut i = 0;
+        while let Some(r) = rotations.get(i).copied() {
+            for next in [r.then(Rotation::X), r.then(Rotation::Y)] {
+                if !rotations.contains(&next) {
+                    rotations.push(next);
+                }
+            }
+            i += 1;
+        }
+        assert_eq!(rotations.len(), 24);
+        rotations
+    };
+}
+
+/// Outer turns with the same effect as `notation`, which may also use wide turns (`r`), slice
+/// turns (`M`, `E`, `S`) and rotations (`x`, `y`, `z`).
+///
+/// Centers never move in `Cube`, so a wide or slice turn is played as the outer turns it differs
+/// from by a rotation, and later turns are relabelled by where that rotation carried each face.
+pub(super) fn outer_turns(notation: &str) -> anyhow::Result<Vec<Move>> {
+    use Face::*;
+
+    // Where each face has been carried to by the rotations so far.
+    let mut frame = Rotation::IDENTITY;
+    let mut moves = Vec::new();
+    for token in notation.split_whitespace() {
+        let mut chars = token.chars();
+        let letter = chars
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No face for move"))?;
+        let direction: Direction = chars.as_str().parse()?;
+
+        let (turns, rotation): (&[(Face, bool)], _) = match letter {
+            'F' => (&[(Front, false)], None),
+            'B' => (&[(Back, false)], None),
+            'L' => (&[(Left, false)], None),
+            'R' => (&[(Right, false)], None),
+            'U' => (&[(Up, false)], None),
+            'D' => (&[(Down, false)], None),
+            'f' => (&[(Back, false)], Some(Rotation::Z)),
+            'b' => (&[(Front, false)], Some(Rotation::Z.inverse())),
+            'l' => (&[(Right, false)], Some(Rotation::X.inverse())),
+            'r' => (&[(Left, false)], Some(Rotation::X)),
+            'u' => (&[(Down, false)], Some(Rotation::Y)),
+            'd' => (&[(Up, false)], Some(Rotation::Y.inverse())),
+            'M' => (&[(Right, false), (Left, true)], Some(Rotation::X.inverse())),
+            'E' => (&[(Up, false), (Down, true)], Some(Rotation::Y.inverse())),
+            'S' => (&[(Front, true), (Back, false)], Some(Rotation::Z)),
+            'x' => (&[], Some(Rotation::X)),
+            'y' => (&[], Some(Rotation::Y)),
+            'z' => (&[], Some(Rotation::Z)),
+            _ => return Err(anyhow::anyhow!("Unrecognized face {}", letter)),
+        };
+
+        for &(face, reversed) in turns {
+            let physical = enum_iterator::all::<Face>()
+                .find(|&f| frame.get(f) == face)
+                .unwrap();
+            let direction = if reversed {
+                direction.reverse()
+            } else {
+                direction
+            };
+            moves.push(Move {
+                face: physical,
+                direction,
+            });
+        }
+        if let Some(rotation) = rotation {
+            let times = match direction {
+                Direction::Single => 1,
+                Direction::Double => 2,
+                Direction::Reverse => 3,
+            };
+            for _ in 0..times {
+                frame = frame.then(rotation);
+            }
+        }
+    }
+    Ok(moves)
+}
+
+/// How a cube is held, as which of its faces, named by their center, points up and which to the
+/// front.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Orientation {
+    up: Face,
+    front: Face,
+}
+
+impl Orientation {
+    pub fn new(up: Face, front: Face) -> anyhow::Result<Orientation> {
+        if Face::same_axis(up, front) {
+            anyhow::bail!("{} and {} can't both point up and to the front", up, front);
+        }
+        Ok(Orientation { up, front })
+    }
+
+    pub fn up(&self) -> Face {
+        self.up
+    }
+
+    pub fn front(&self) -> Face {
+        self.front
+    }
+
+    /// The rotation carrying each of the cube's faces to where it points.
+    fn rotation(&self) -> Rotation {
+        *ROTATIONS
+            .iter()
+            .find(|r| r.get(self.up) == Face::Up && r.get(self.front) == Face::Front)
+            .unwrap()
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Orientation {
+            up: Face::Up,
+            front: Face::Front,
+        }
+    }
+}
+
+impl Cube {
+    /// The cube relabelled by where its faces point when held in `orientation`.
+    ///
+    /// Moves on the result name the faces of that frame, so an `Up` turn turns whichever face
+    /// points up. It is solved when every face matches the center it's held with.
+    pub fn held(&self, orientation: Orientation) -> Cube {
+        self.rotated(orientation.rotation())
+    }
+
+    /// The cube as seen from each of its 24 orientations.
+    ///
+    /// Centers are fixed in this representation, so stickers are recolored along with the
+    /// rotation. A cube's orientations are the same state up to relabelling, and the solved cube
+    /// is its own orientation in all of them.
+    pub fn orientations(&self) -> impl Iterator<Item = Cube> + '_ {
+        ROTATIONS.iter().map(move |r| self.rotated(*r))
+    }
+
+    /// The smallest of the cube's `orientations`, comparing stickers face by face. Cubes that
+    /// differ only by a whole-cube rotation have the same canonical form.
+    pub fn canonical(&self) -> Cube {
+        self.orientations().min().unwrap()
+    }
+
+    fn rotated(&self, rotation: Rotation) -> Cube {
+        let mut cube = Cube::solved();
+        for location in Location::all() {
+            if let Location::Center(_) = location {
+                continue;
+            }
+            cube.set(
+                rotation.location(location),
+                rotation.get(self.get(location)),
+            );
+        }
+        cube
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn solved_is_every_orientation() {
+        assert!(Cube::solved().orientations().all(|c| c == Cube::solved()));
+    }
+
+    #[test]
+    fn scrambled_has_24_orientations() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B F D");
+        let orientations = cube.orientations().collect::<HashSet<_>>();
+        assert_eq!(orientations.len(), 24);
+    }
+
+    #[quickcheck]
+    fn canonical_ignores_rotation(moves: Vec<Move>, rotation: usize) -> bool {
+        let cube = Cube::solved().apply_all(moves);
+        let rotated = cube.rotated(ROTATIONS[rotation % ROTATIONS.len()]);
+        rotated.canonical() == cube.canonical()
+    }
+
+    #[test]
+    fn canonical_is_an_orientation() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+        assert!(cube.orientations().any(|c| c == cube.canonical()));
+    }
+
+    #[test]
+    fn rotating_a_turn_is_another_turn() {
+        assert_eq!(
+            cube_with_moves("R").rotated(Rotation::Y),
+            cube_with_moves("F")
+        );
+        assert_eq!(
+            cube_with_moves("R").rotated(Rotation::X),
+            cube_with_moves("R")
+        );
+    }
+
+    #[test]
+    fn held_upside_down() {
+        let orientation = Orientation::new(Face::Down, Face::Front).unwrap();
+        assert_eq!(
+            cube_with_moves("R U").held(orientation),
+            cube_with_moves("L D")
+        );
+    }
+
+    #[test]
+    fn held_by_default_is_unchanged() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+        assert_eq!(cube.held(Orientation::default()), cube);
+    }
+
+    #[test]
+    fn orientations_need_perpendicular_faces() {
+        assert!(Orientation::new(Face::Up, Face::Down).is_err());
+        assert!(Orientation::new(Face::Left, Face::Left).is_err());
+    }
+
+    #[test]
+    fn rotating_like_a_front_turn() {
+        assert_eq!(
+            cube_with_moves("U").rotated(Rotation::Z),
+            cube_with_moves("R")
+        );
+    }
+
+    #[test]
+    fn outer_turns_pass_through() {
+        assert_eq!(
+            outer_turns("R U2 F'").unwrap(),
+            Move::parse_sequence("R U2 F'").unwrap()
+        );
+    }
+
+    #[test]
+    fn rotations_relabel_later_turns() {
+        assert_eq!(outer_turns("x U").unwrap(), moves![F]);
+        assert_eq!(outer_turns("y R").unwrap(), moves![B]);
+        assert_eq!(outer_turns("z U").unwrap(), moves![L]);
+    }
+
+    #[test]
+    fn wide_and_slice_turns() {
+        assert_eq!(
+            outer_turns("M2 U M2 U2 M2 U M2").unwrap(),
+            Move::parse_sequence("R2 L2 D R2 L2 U2 R2 L2 D R2 L2").unwrap()
+        );
+        assert_eq!(
+            outer_turns("r U R' U' r' F R F'").unwrap(),
+            Move::parse_sequence("L F R' F' L' F R F'").unwrap()
+        );
+        assert_eq!(
+            outer_turns("S E' f'").unwrap(),
+            Move::parse_sequence("F' B L' R D'").unwrap()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::prelude::*;
+
+/// Stages of a layer by layer solve starting from `face`, each including the ones before it.
+impl Cube {
+    /// Whether the four edges around `face` are solved, ignoring the rest of the cube.
+    pub fn cross_solved(&self, face: Face) -> bool {
+        face.adjacent().iter().all(|&side| {
+            self.get(Location::Edge(face, side)) == face
+                && self.get(Location::Edge(side, face)) == side
+        })
+    }
+
+    /// Whether everything outside the layer opposite `face` is solved.
+    pub fn f2l_solved(&self, face: Face) -> bool {
+        Location::all()
+            .filter(|&l| !in_layer(l, face.opposite()))
+            .all(|l| self.get(l) == l.face())
+    }
+
+    /// Whether the first two layers from `face` are solved and the opposite face is a single
+    /// color, so only the last layer's permutation is left.
+    pub fn last_layer_oriented(&self, face: Face) -> bool {
+        let last = face.opposite();
+        self.f2l_solved(face)
+            && Location::all()
+                .filter(|&l| l.face() == last)
+                .all(|l| self.get(l) == last)
+    }
+}
+
+/// Whether the location is on a piece that turns with `face`.
+pub(super) fn in_layer(location: Location, face: Face) -> bool {
+    match location {
+        Location::Center(f) => f == face,
+        Location::Edge(a, b) => [a, b].contains(&face),
+        Location::Corner(a, b, c) => [a, b, c].contains(&face),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_passes_every_stage() {
+        let cube = Cube::solved();
+        for face in enum_iterator::all::<Face>() {
+            assert!(cube.cross_solved(face));
+            assert!(cube.f2l_solved(face));
+            assert!(cube.last_layer_oriented(face));
+        }
+    }
+
+    #[test]
+    fn pair_insertion_keeps_cross() {
+        let cube = cube_with_moves("R U R'");
+        assert!(cube.cross_solved(Face::Down));
+        assert!(!cube.f2l_solved(Face::Down));
+        assert!(!cube.cross_solved(Face::Up));
+    }
+
+    #[test]
+    fn sune_keeps_f2l() {
+        let cube = cube_with_moves("R U R' U R U2 R'");
+        assert!(cube.f2l_solved(Face::Down));
+        assert!(!cube.last_layer_oriented(Face::Down));
+        assert!(!cube.f2l_solved(Face::Up));
+    }
+
+    #[test]
+    fn t_perm_keeps_orientation() {
+        let cube = cube_with_moves("R U R' U' R' F R2 U' R' U' R U R' F'");
+        assert!(cube.last_layer_oriented(Face::Down));
+        assert_ne!(cube, Cube::solved());
+    }
+
+    #[test]
+    fn stages_are_relative_to_the_face() {
+        // Sune and the T perm turned upside down, solving the last layer on D.
+        let sune = cube_with_moves("R D R' D R D2 R'");
+        assert!(sune.cross_solved(Face::Up));
+        assert!(sune.f2l_solved(Face::Up));
+        assert!(!sune.last_layer_oriented(Face::Up));
+
+        let t_perm = cube_with_moves("R D R' D' R' B R2 D' R' D' R D R' B'");
+        assert!(t_perm.last_layer_oriented(Face::Up));
+        assert!(!t_perm.f2l_solved(Face::Down));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::cube::coord::CoordCube;
+use crate::prelude::*;
+
+/// Moves that keep a cube within the domino group, ⟨U, D, F2, B2, L2, R2⟩.
+pub fn domino_moves() -> impl Iterator<Item = Move> {
+    Move::all().filter(is_domino_move)
+}
+
+pub fn is_domino_move(m: &Move) -> bool {
+    matches!(
+        (m.face, m.direction),
+        (Face::Up | Face::Down, _) | (_, Direction::Double)
+    )
+}
+
+/// How far from solved `moves_preserving` samples cubes in the group.
+const SAMPLE_DEPTH: usize = 3;
+
+/// The moves that keep cubes satisfying `predicate` satisfying it, for a group containing
+/// solved given as a membership test.
+///
+/// Moves can't be checked against every cube in a group, so they're checked against samples:
+/// the cubes within `SAMPLE_DEPTH` moves of solved, using only moves still believed to
+/// preserve the group. Any move that takes a sample out is dropped, and sampling repeats until
+/// nothing more is. A move that only fails on cubes further away than that is wrongly kept.
+pub fn moves_preserving(predicate: impl Fn(&Cube) -> bool) -> Vec<Move> {
+    let mut moves = Move::all()
+        .filter(|&m| predicate(&Cube::solved().apply(m)))
+        .collect::<Vec<_>>();
+
+    loop {
+        let mut samples = vec![Cube::solved()];
+        let mut frontier = samples.clone();
+        for _ in 0..SAMPLE_DEPTH {
+            frontier = frontier
+                .iter()
+                .flat_map(|cube| moves.iter().map(|&m| cube.clone().apply(m)))
+                .filter(|cube| predicate(cube))
+                .collect();
+            samples.extend(frontier.iter().cloned());
+        }
+
+        let preserving = moves
+            .iter()
+            .copied()
+            .filter(|&m| samples.iter().all(|s| predicate(&s.clone().apply(m))))
+            .collect::<Vec<_>>();
+        if preserving == moves {
+            return moves;
+        }
+        moves = preserving;
+    }
+}
+
+/// Whether the cube can be solved using only `domino_moves`.
+///
+/// Every U/D sticker is on the U or D face, and the middle layer edges are not flipped.
+pub fn is_domino_cube(cube: &Cube) -> bool {
+    use Face::*;
+
+    Location::all().all(|l| {
+        matches!(
+            (l, cube.get(l)),
+            (Location::Center(_), _)
+                | (Location::Edge(Up | Down, _), Up | Down)
+                | (Location::Corner(Up | Down, _, _), Up | Down)
+                | (Location::Edge(Front | Back, Left | Right), Front | Back)
+                | (Location::Edge(Left | Right, Front | Back), _)
+                | (Location::Edge(Front | Back | Left | Right, Up | Down), _)
+                | (Location::Corner(Front | Back | Left | Right, _, _), _)
+        )
+    })
+}
+
+/// The nested subgroups of Thistlethwaite's algorithm, from least to most solved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SolveGroup {
+    /// Any cube.
+    G0,
+    /// Edges are oriented, solvable with ⟨U, D, L, R, F2, B2⟩.
+    G1,
+    /// The domino group, solvable with ⟨U, D, L2, R2, F2, B2⟩.
+    G2,
+    /// Solvable with half turns alone.
+    G3,
+    Solved,
+}
+
+impl Cube {
+    /// The smallest of the `SolveGroup`s the cube is in.
+    pub fn group(&self) -> SolveGroup {
+        let coord = CoordCube::from(self.clone());
+        if coord.is_solved() {
+            SolveGroup::Solved
+        } else if coord.is_half_turn() {
+            SolveGroup::G3
+        } else if coord.is_domino() {
+            SolveGroup::G2
+        } else if coord.edge_orientation() == 0 {
+            SolveGroup::G1
+        } else {
+            SolveGroup::G0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_is_domino() {
+        assert!(is_domino_cube(&Cube::solved()));
+    }
+
+    #[test]
+    fn quarter_turn_is_not_domino() {
+        assert!(!is_domino_cube(&cube_with_moves("R")));
+    }
+
+    #[test]
+    fn flipped_slice_edges_are_not_domino() {
+        let mut cube = Cube::solved();
+        for (a, b) in [(Face::Front, Face::Right), (Face::Back, Face::Left)] {
+            cube.set(Location::Edge(a, b), b);
+            cube.set(Location::Edge(b, a), a);
+        }
+        assert!(!is_domino_cube(&cube));
+    }
+
+    #[test]
+    fn ten_domino_moves() {
+        assert_eq!(domino_moves().count(), 10);
+    }
+
+    #[test]
+    fn rediscovers_domino_moves() {
+        assert_eq!(
+            moves_preserving(is_domino_cube),
+            domino_moves().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn moves_preserving_other_groups() {
+        let half_turns = moves_preserving(|c| CoordCube::from(c.clone()).is_half_turn());
+        assert_eq!(half_turns, moves![F2, B2, L2, R2, U2, D2]);
+
+        let solved = moves_preserving(|c| *c == Cube::solved());
+        assert_eq!(solved, vec![]);
+    }
+
+    #[quickcheck]
+    fn domino_moves_stay_domino(moves: Vec<Move>) -> bool {
+        let moves = moves.into_iter().filter(is_domino_move);
+        is_domino_cube(&Cube::solved().apply_all(moves))
+    }
+
+    #[test]
+    fn groups_of_known_algorithms() {
+        assert_eq!(Cube::solved().group(), SolveGroup::Solved);
+        assert_eq!(cube_with_moves("R2 U2 F2").group(), SolveGroup::G3);
+        assert_eq!(cube_with_moves("R2 U").group(), SolveGroup::G2);
+        assert_eq!(cube_with_moves("R U").group(), SolveGroup::G1);
+        assert_eq!(cube_with_moves("F").group(), SolveGroup::G0);
+    }
+
+    #[quickcheck]
+    fn half_turns_stay_in_g3(moves: Vec<Move>) -> bool {
+        let moves = moves
+            .into_iter()
+            .filter(|m| m.direction == Direction::Double);
+        Cube::solved().apply_all(moves).group() >= SolveGroup::G3
+    }
+
+    #[quickcheck]
+    fn domino_moves_stay_in_g2(moves: Vec<Move>) -> bool {
+        let moves = moves.into_iter().filter(is_domino_move);
+        Cube::solved().apply_all(moves).group() >= SolveGroup::G2
+    }
+}
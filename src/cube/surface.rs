@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Cube<F = Face> {
     up: Surface<F>,
     down: Surface<F>,
@@ -10,6 +10,14 @@ pub struct Cube<F = Face> {
     right: Surface<F>,
 }
 
+/// Hashes the packed form, six words rather than a discriminant per sticker, which keeps the
+/// hash-heavy searches like `Mitm` from spending most of their time in the hasher.
+impl core::hash::Hash for Cube {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        PackedCube::from(self).hash(state);
+    }
+}
+
 impl super::CubeLike for Cube {
     fn solved() -> Cube {
         Cube {
@@ -29,6 +37,18 @@ impl super::CubeLike for Cube {
 }
 
 impl Cube {
+    pub fn apply_str(self, moves: &str) -> anyhow::Result<Cube> {
+        Ok(self.apply_all(Move::parse_sequence(moves)?))
+    }
+
+    /// The cube after each of `moves` in turn, not including the starting state.
+    pub fn scan(self, moves: impl IntoIterator<Item = Move>) -> impl Iterator<Item = Cube> {
+        moves.into_iter().scan(self, |cube, m| {
+            cube.rotate(m);
+            Some(cube.clone())
+        })
+    }
+
     pub fn rotate(&mut self, move_: impl Into<Move>) {
         let move_ = move_.into();
 
@@ -68,6 +88,11 @@ impl Cube {
         }
     }
 
+    /// Turn `face` in `direction`, the same as rotating by that `Move`.
+    pub fn turn(&mut self, face: Face, direction: Direction) {
+        self.rotate((face, direction));
+    }
+
     fn surface(&self, face: Face) -> &Surface {
         match face {
             Face::Up => &self.up,
@@ -91,7 +116,7 @@ impl Cube {
     }
 
     #[inline(never)]
-    fn slices(&mut self, face: Face) -> [SliceMut; 4] {
+    fn slices(&mut self, face: Face) -> [SliceMut<'_>; 4] {
         match face {
             Face::Up => [
                 self.left.top_mut(),
@@ -132,87 +157,269 @@ impl Cube {
         }
     }
 
-    pub fn get(&self, location: Location) -> Face {
-        use Face::*;
+    /// The stickers around `face`'s center, clockwise from its top left.
+    pub(super) fn stickers(&self, face: Face) -> [Face; 8] {
+        self.surface(face).0
+    }
+
+    /// The cube with `stickers(face)` around each face, the inverse of `stickers`.
+    pub(super) fn from_stickers(stickers: impl Fn(Face) -> [Face; 8]) -> Cube {
+        Cube {
+            up: Surface(stickers(Face::Up)),
+            down: Surface(stickers(Face::Down)),
+            front: Surface(stickers(Face::Front)),
+            back: Surface(stickers(Face::Back)),
+            left: Surface(stickers(Face::Left)),
+            right: Surface(stickers(Face::Right)),
+        }
+    }
 
+    pub fn get(&self, location: Location) -> Face {
         match location {
             Location::Center(f) => f,
+            Location::Edge(s, ..) | Location::Corner(s, ..) => {
+                self.surface(s).0[sticker_index(location)]
+            }
+        }
+    }
+
+    /// Like `get`, but `None` rather than panicking when `location`'s faces don't name a
+    /// sticker, as with a corner of two faces on one axis or faces in an order `get` doesn't use.
+    pub fn try_get(&self, location: Location) -> Option<Face> {
+        match location {
+            Location::Center(f) => Some(f),
+            Location::Edge(s, ..) | Location::Corner(s, ..) => {
+                try_sticker_index(location).map(|i| self.surface(s).0[i])
+            }
+        }
+    }
+
+    /// Overwrite a single sticker, the inverse of `get`.
+    ///
+    /// Centers are fixed, so setting one to anything other than its own face panics. Nothing
+    /// checks that the result is a solvable cube.
+    pub fn set(&mut self, location: Location, face: Face) {
+        match location {
+            Location::Center(f) => assert_eq!(f, face, "Centers cannot be moved"),
+            Location::Edge(s, ..) | Location::Corner(s, ..) => {
+                self.surface_mut(s).0[sticker_index(location)] = face
+            }
+        }
+    }
+
+    /// Whether every edge and corner sticker matches, as on a stickerless cube where centers
+    /// can't be told apart.
+    ///
+    /// Centers never move under face turns, so for now this agrees with `==`.
+    pub fn eq_ignoring_centers(&self, other: &Cube) -> bool {
+        Location::all()
+            .filter(|l| !matches!(l, Location::Center(_)))
+            .all(|l| self.get(l) == other.get(l))
+    }
+
+    /// Every sticker where the cubes disagree, with its face on `self` and then on `other`.
+    pub fn diff(&self, other: &Cube) -> Vec<(Location, Face, Face)> {
+        Location::all()
+            .map(|l| (l, self.get(l), other.get(l)))
+            .filter(|(_, a, b)| a != b)
+            .collect()
+    }
+
+    /// How many stickers aren't on their own face, a rough measure of how far from solved the
+    /// cube is.
+    pub fn misplaced_stickers(&self) -> usize {
+        self.diff(&Cube::solved()).len()
+    }
+}
 
-            Location::Edge(s, against) => {
-                let index = match (s, against) {
-                    (_, Up) => 1,
-                    (_, Down) => 5,
+/// Index of a non-center sticker within its `Surface`, clockwise from the top left.
+pub(super) fn sticker_index(location: Location) -> usize {
+    match location {
+        Location::Center(_) => unreachable!("centers are not stored in a surface"),
+        _ => try_sticker_index(location).unwrap_or_else(|| unreachable!("{:?}", location)),
+    }
+}
 
-                    (Front, Left) => 7,
-                    (Front, Right) => 3,
+/// Like `sticker_index`, but `None` for centers and for faces that don't name a sticker.
+pub(super) fn try_sticker_index(location: Location) -> Option<usize> {
+    use Face::*;
 
-                    (Back, Left) => 3,
-                    (Back, Right) => 7,
+    let index = match location {
+        Location::Center(_) => return None,
 
-                    (Left, Front) => 3,
-                    (Left, Back) => 7,
+        Location::Edge(s, against) if Face::same_axis(s, against) => return None,
+        Location::Edge(s, against) => match (s, against) {
+            (_, Up) => 1,
+            (_, Down) => 5,
 
-                    (Right, Front) => 7,
-                    (Right, Back) => 3,
+            (Front, Left) => 7,
+            (Front, Right) => 3,
 
-                    (Up | Down, Left) => 7,
-                    (Up | Down, Right) => 3,
+            (Back, Left) => 3,
+            (Back, Right) => 7,
 
-                    (Up, Front) => 5,
-                    (Up, Back) => 1,
+            (Left, Front) => 3,
+            (Left, Back) => 7,
 
-                    (Down, Front) => 1,
-                    (Down, Back) => 5,
+            (Right, Front) => 7,
+            (Right, Back) => 3,
 
-                    _ => unreachable!(),
-                };
+            (Up | Down, Left) => 7,
+            (Up | Down, Right) => 3,
+
+            (Up, Front) => 5,
+            (Up, Back) => 1,
+
+            (Down, Front) => 1,
+            (Down, Back) => 5,
+
+            _ => return None,
+        },
+
+        Location::Corner(s, e, p) => match (s, e, p) {
+            (Front, Left, Up) => 0,
+            (Front, Left, Down) => 6,
+            (Front, Right, Up) => 2,
+            (Front, Right, Down) => 4,
+
+            (Back, Left, Up) => 2,
+            (Back, Left, Down) => 4,
+            (Back, Right, Up) => 0,
+            (Back, Right, Down) => 6,
+
+            (Left, Front, Up) => 2,
+            (Left, Front, Down) => 4,
+            (Left, Back, Up) => 0,
+            (Left, Back, Down) => 6,
+
+            (Right, Front, Up) => 0,
+            (Right, Front, Down) => 6,
+            (Right, Back, Up) => 2,
+            (Right, Back, Down) => 4,
+
+            (Up, Front, Left) => 6,
+            (Up, Front, Right) => 4,
+            (Up, Back, Left) => 0,
+            (Up, Back, Right) => 2,
+
+            (Down, Front, Left) => 0,
+            (Down, Front, Right) => 2,
+            (Down, Back, Left) => 6,
+            (Down, Back, Right) => 4,
+
+            _ => return None,
+        },
+    };
+    Some(index)
+}
+
+impl std::fmt::Display for Cube {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.write_net(f, |f, face| write!(f, "{}", face))
+    }
+}
 
-                self.surface(s).0[index]
+impl Cube {
+    /// Renders the same net as `Display`, with an ANSI background color behind each sticker.
+    pub fn display_colored(&self) -> Colored<'_> {
+        self.display_with(ColorScheme::default())
+    }
+
+    /// Like `display_colored`, labelled and colored by `scheme`. The default scheme's colors keep
+    /// their standard entries in the 256-color palette, any others get the nearest entry. Use
+    /// `Colored::truecolor` for the scheme's exact ones.
+    pub fn display_with(&self, scheme: ColorScheme) -> Colored<'_> {
+        Colored {
+            cube: self,
+            scheme,
+            truecolor: false,
+        }
+    }
+
+    /// The 54 sticker labels in the conventional facelet order: the U, R, F, D, L then B faces,
+    /// each read row by row as laid out in the `Display` net.
+    pub fn to_facelets(&self, scheme: &ColorScheme) -> String {
+        FACELET_FACES
+            .iter()
+            .flat_map(|&face| {
+                FACELET_INDICES.iter().map(move |index| match index {
+                    Some(i) => self.surface(face).0[*i],
+                    None => face,
+                })
+            })
+            .map(|face| scheme.get(face).label)
+            .collect()
+    }
+
+    pub fn from_facelets(facelets: &str, scheme: &ColorScheme) -> anyhow::Result<Cube> {
+        let faces = facelets
+            .chars()
+            .map(|c| {
+                scheme
+                    .face_with_label(c)
+                    .ok_or_else(|| anyhow::anyhow!("Unrecognized facelet {}", c))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if faces.len() != 54 {
+            anyhow::bail!("Expected 54 facelets, got {}", faces.len());
+        }
+
+        let mut cube = Cube::solved();
+        for (&face, stickers) in FACELET_FACES.iter().zip(faces.chunks(9)) {
+            for (index, &sticker) in FACELET_INDICES.iter().zip(stickers) {
+                match index {
+                    Some(i) => cube.surface_mut(face).0[*i] = sticker,
+                    None if sticker != face => {
+                        anyhow::bail!("Expected {} center, got {}", face, sticker)
+                    }
+                    None => {}
+                }
             }
+        }
+        Ok(cube)
+    }
 
-            Location::Corner(s, e, p) => {
-                let index = match (s, e, p) {
-                    (Front, Left, Up) => 0,
-                    (Front, Left, Down) => 6,
-                    (Front, Right, Up) => 2,
-                    (Front, Right, Down) => 4,
-
-                    (Back, Left, Up) => 2,
-                    (Back, Left, Down) => 4,
-                    (Back, Right, Up) => 0,
-                    (Back, Right, Down) => 6,
-
-                    (Left, Front, Up) => 2,
-                    (Left, Front, Down) => 4,
-                    (Left, Back, Up) => 0,
-                    (Left, Back, Down) => 6,
-
-                    (Right, Front, Up) => 0,
-                    (Right, Front, Down) => 6,
-                    (Right, Back, Up) => 2,
-                    (Right, Back, Down) => 4,
-
-                    (Up, Front, Left) => 6,
-                    (Up, Front, Right) => 4,
-                    (Up, Back, Left) => 0,
-                    (Up, Back, Right) => 2,
-
-                    (Down, Front, Left) => 0,
-                    (Down, Front, Right) => 2,
-                    (Down, Back, Left) => 6,
-                    (Down, Back, Right) => 4,
-
-                    _ => unreachable!("{:?}", location),
-                };
+    /// The `Display` net on a fixed grid: every line is 12 characters, with each face in its own
+    /// 3-wide column and spaces where there is none, so nets line up for diffing.
+    pub fn net_string(&self) -> String {
+        let bands = [
+            [None, Some((&self.up, Face::Up)), None, None],
+            [
+                Some((&self.left, Face::Left)),
+                Some((&self.front, Face::Front)),
+                Some((&self.right, Face::Right)),
+                Some((&self.back, Face::Back)),
+            ],
+            [None, Some((&self.down, Face::Down)), None, None],
+        ];
 
-                self.surface(s).0[index]
+        let mut net = String::new();
+        for band in bands {
+            for row in 0..3 {
+                for block in band {
+                    match block {
+                        None => net.push_str("   "),
+                        Some((surface, face)) => {
+                            let slice = match row {
+                                0 => surface.top(),
+                                1 => surface.mid_horizontal(face),
+                                _ => surface.bottom(),
+                            };
+                            net.extend(slice.0.iter().map(|f| f.to_string()));
+                        }
+                    }
+                }
+                net.push('\n');
             }
         }
+        net
     }
-}
 
-impl std::fmt::Display for Cube {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn write_net(
+        &self,
+        f: &mut std::fmt::Formatter,
+        sticker: impl Fn(&mut std::fmt::Formatter, Face) -> std::fmt::Result,
+    ) -> std::fmt::Result {
         let slices = |surface: &Surface, face: Face| {
             [
                 surface.top(),
@@ -222,7 +429,9 @@ impl std::fmt::Display for Cube {
         };
 
         for slice in slices(&self.up, Face::Up).iter() {
-            writeln!(f, "    {}", slice)?;
+            write!(f, "    ")?;
+            slice.write_with(f, &sticker)?;
+            writeln!(f)?;
         }
 
         let middle_slices = [
@@ -237,52 +446,137 @@ impl std::fmt::Display for Cube {
 
         for index in 0..3 {
             for slice_list in &middle_slices {
-                write!(f, "{} ", slice_list[index])?;
+                slice_list[index].write_with(f, &sticker)?;
+                write!(f, " ")?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         for slice in slices(&self.down, Face::Down).iter() {
-            writeln!(f, "    {}", slice)?;
+            write!(f, "    ")?;
+            slice.write_with(f, &sticker)?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+const FACELET_FACES: [Face; 6] = [
+    Face::Up,
+    Face::Right,
+    Face::Front,
+    Face::Down,
+    Face::Left,
+    Face::Back,
+];
+
+/// Surface indices of a face's stickers read row by row, with `None` for the center.
+const FACELET_INDICES: [Option<usize>; 9] = [
+    Some(0),
+    Some(1),
+    Some(2),
+    Some(7),
+    None,
+    Some(3),
+    Some(6),
+    Some(5),
+    Some(4),
+];
+
+pub struct Colored<'c> {
+    cube: &'c Cube,
+    scheme: ColorScheme,
+    truecolor: bool,
+}
+
+impl Colored<'_> {
+    /// Color with the scheme's exact RGB values, for terminals that support 24-bit color.
+    pub fn truecolor(mut self) -> Self {
+        self.truecolor = true;
+        self
+    }
+}
+
+impl std::fmt::Display for Colored<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.cube.write_net(f, |f, face| {
+            let sticker = self.scheme.get(face);
+            let Sticker {
+                label,
+                rgb: (r, g, b),
+            } = sticker;
+            if self.truecolor {
+                write!(f, "\x1b[30;48;2;{};{};{}m{}\x1b[0m", r, g, b, label)
+            } else {
+                let color = if sticker.rgb == ColorScheme::default().get(face).rgb {
+                    ansi_color(face)
+                } else {
+                    sticker.ansi256()
+                };
+                write!(f, "\x1b[30;48;5;{}m{}\x1b[0m", color, label)
+            }
+        })
+    }
+}
+
+fn ansi_color(face: Face) -> u8 {
+    match face {
+        Face::Up => 231,
+        Face::Down => 226,
+        Face::Front => 34,
+        Face::Back => 21,
+        Face::Left => 208,
+        Face::Right => 196,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 struct Surface<F = Face>([F; 8]);
 
+// The rotations transmute a `Surface` to and from a `u64`.
+const _: () = assert!(std::mem::size_of::<Face>() == 1);
+const _: () = assert!(std::mem::size_of::<Surface>() == 8);
+
 impl Surface {
     fn rotate(&mut self) {
-        unsafe {
-            let as_int: u64 = std::mem::transmute(*self);
-            let rotated = as_int.rotate_left(16);
-            *self = std::mem::transmute(rotated);
-        }
+        self.rotate_by(1);
     }
 
     fn rotate_reverse(&mut self) {
-        unsafe {
-            let as_int: u64 = std::mem::transmute(*self);
-            let rotated = as_int.rotate_right(16);
-            *self = std::mem::transmute(rotated);
-        }
+        self.rotate_by(3);
     }
 
     fn rotate_double(&mut self) {
+        self.rotate_by(2);
+    }
+
+    fn rotate_by(&mut self, quarter_turns: u32) {
+        // Rotating the packed bytes only matches the array order on little-endian targets.
+        if cfg!(any(feature = "safe", target_endian = "big")) {
+            self.rotate_safe(quarter_turns);
+        } else {
+            self.rotate_packed(quarter_turns);
+        }
+    }
+
+    fn rotate_packed(&mut self, quarter_turns: u32) {
         unsafe {
-            let as_int: u64 = std::mem::transmute(*self);
-            let rotated = as_int.rotate_right(32);
-            *self = std::mem::transmute(rotated);
+            let as_int = std::mem::transmute::<Surface, u64>(*self);
+            let rotated = as_int.rotate_left(16 * quarter_turns);
+            *self = std::mem::transmute::<u64, Surface>(rotated);
         }
     }
 
+    fn rotate_safe(&mut self, quarter_turns: u32) {
+        self.0.rotate_right(2 * quarter_turns as usize);
+    }
+
     fn top(&self) -> Slice {
         Slice([self.0[0], self.0[1], self.0[2]])
     }
 
-    fn top_mut(&mut self) -> SliceMut {
+    fn top_mut(&mut self) -> SliceMut<'_> {
         self.slice_mut(0, 1, 2)
     }
 
@@ -308,15 +602,15 @@ impl Surface {
         Slice([self.0[6], self.0[5], self.0[4]])
     }
 
-    fn bottom_mut(&mut self) -> SliceMut {
+    fn bottom_mut(&mut self) -> SliceMut<'_> {
         self.slice_mut(4, 5, 6)
     }
 
-    fn right_mut(&mut self) -> SliceMut {
+    fn right_mut(&mut self) -> SliceMut<'_> {
         self.slice_mut(2, 3, 4)
     }
 
-    fn left_mut(&mut self) -> SliceMut {
+    fn left_mut(&mut self) -> SliceMut<'_> {
         self.slice_mut(6, 7, 0)
     }
 }
@@ -330,9 +624,13 @@ impl From<Face> for Surface {
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 struct Slice([Face; 3]);
 
-impl std::fmt::Display for Slice {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}{}{}", self.0[0], self.0[1], self.0[2])
+impl Slice {
+    fn write_with(
+        &self,
+        f: &mut std::fmt::Formatter,
+        sticker: impl Fn(&mut std::fmt::Formatter, Face) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        self.0.iter().try_for_each(|&face| sticker(f, face))
     }
 }
 
@@ -346,10 +644,10 @@ impl<'s> SliceMut<'s> {
         use core::mem::{transmute, MaybeUninit};
 
         let mut array: [MaybeUninit<Face>; 3] = unsafe { MaybeUninit::uninit().assume_init() };
-        for i in 0..3 {
-            array[i] = MaybeUninit::new(self.surface.0[self.indices[i] as usize]);
+        for (slot, index) in array.iter_mut().zip(self.indices) {
+            *slot = MaybeUninit::new(self.surface.0[index as usize]);
         }
-        Slice(unsafe { transmute::<_, [Face; 3]>(array) })
+        Slice(unsafe { transmute::<[MaybeUninit<Face>; 3], [Face; 3]>(array) })
     }
 
     fn set(&mut self, owned: Slice) {
@@ -364,6 +662,21 @@ mod tests {
     use super::*;
     use Face::*;
 
+    #[test]
+    fn scan_yields_each_state() {
+        let states = cube_with_moves("F")
+            .scan(Move::parse_sequence("R U' D2").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                cube_with_moves("F R"),
+                cube_with_moves("F R U'"),
+                cube_with_moves("F R U' D2"),
+            ]
+        );
+    }
+
     #[test]
     fn rotate_surface() {
         let mut surface = Surface([Left, Left, Up, Up, Right, Right, Down, Down]);
@@ -394,15 +707,314 @@ mod tests {
         );
     }
 
+    #[cfg(target_endian = "little")]
+    #[quickcheck]
+    fn safe_rotation_matches_packed(faces: Vec<Face>) -> bool {
+        let surface = Surface(core::array::from_fn(|i| {
+            faces.get(i).copied().unwrap_or(Face::Up)
+        }));
+
+        (1..=3).all(|quarter_turns| {
+            let mut safe = surface;
+            safe.rotate_safe(quarter_turns);
+            let mut packed = surface;
+            packed.rotate_packed(quarter_turns);
+            safe == packed
+        })
+    }
+
+    fn hash_of(cube: &Cube) -> u64 {
+        use core::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        cube.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[quickcheck]
+    fn equal_cubes_hash_equal(moves: Vec<Move>, detour: Vec<Move>) -> bool {
+        let direct = Cube::solved().apply_all(moves.clone());
+        let roundabout = Cube::solved()
+            .apply_all(moves)
+            .apply_all(detour.clone())
+            .apply_all(Move::invert_sequence(&detour));
+
+        direct == roundabout && hash_of(&direct) == hash_of(&roundabout)
+    }
+
     #[test]
     fn rotate_cube() {
         let cube = Cube::solved().apply("F".parse().unwrap());
         assert_eq!(cube.up, Surface([Up, Up, Up, Up, Left, Left, Left, Up]));
     }
 
+    #[test]
+    fn turn_is_rotate() {
+        let mut cube = Cube::solved();
+        cube.turn(Right, Direction::Double);
+        assert_eq!(cube, cube_with_moves("R2"));
+
+        cube.rotate((Up, Direction::Reverse));
+        assert_eq!(cube, cube_with_moves("R2 U'"));
+    }
+
+    #[test]
+    fn apply_str_matches_apply_all() {
+        let moves = Move::parse_sequence("R2 U' L2").unwrap();
+        assert_eq!(
+            Cube::solved().apply_str("R2 U' L2").unwrap(),
+            Cube::solved().apply_all(moves)
+        );
+    }
+
+    #[test]
+    fn apply_str_propagates_parse_errors() {
+        assert!(Cube::solved().apply_str("R2 X L2").is_err());
+    }
+
+    #[quickcheck]
+    fn eq_ignoring_centers_agrees_with_eq(a: Vec<Move>, b: Vec<Move>) -> bool {
+        let a = Cube::solved().apply_all(a.into_iter().take(3));
+        let b = Cube::solved().apply_all(b.into_iter().take(3));
+        a.eq_ignoring_centers(&b) == (a == b)
+    }
+
+    #[test]
+    fn eq_ignoring_centers_sees_one_sticker() {
+        let mut cube = cube_with_moves("R U");
+        assert!(cube.eq_ignoring_centers(&cube_with_moves("R U")));
+
+        cube.set(Location::Edge(Up, Front), Down);
+        assert!(!cube.eq_ignoring_centers(&cube_with_moves("R U")));
+    }
+
+    #[test]
+    fn diff_of_r_is_its_ring() {
+        let diff = Cube::solved().diff(&cube_with_moves("R"));
+
+        // R's own stickers stay R on a solved cube, the 12 around it move.
+        let ring = Location::all()
+            .filter(|&l| l.face() != Right)
+            .filter(|&l| match l {
+                Location::Center(_) => false,
+                Location::Edge(_, b) => b == Right,
+                Location::Corner(_, b, c) => b == Right || c == Right,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(diff.len(), 12);
+        assert_eq!(diff.iter().map(|&(l, ..)| l).collect::<Vec<_>>(), ring);
+
+        let (location, solved, moved) = diff[0];
+        assert_eq!(solved, location.face());
+        assert_eq!(moved, cube_with_moves("R").get(location));
+    }
+
+    #[test]
+    fn diff_of_equal_cubes_is_empty() {
+        let cube = cube_with_moves("R U F'");
+        assert_eq!(cube.diff(&cube), vec![]);
+    }
+
+    #[test]
+    fn misplaced_stickers_of_r() {
+        assert_eq!(Cube::solved().misplaced_stickers(), 0);
+        assert_eq!(cube_with_moves("R").misplaced_stickers(), 12);
+    }
+
+    #[test]
+    fn try_get_matches_get() {
+        let cube = cube_with_moves("R2 U' L2");
+        for location in Location::all() {
+            assert_eq!(cube.try_get(location), Some(cube.get(location)));
+        }
+    }
+
+    #[test]
+    fn try_get_rejects_invalid_locations() {
+        let cube = Cube::solved();
+        assert_eq!(cube.try_get(Location::Corner(Up, Down, Front)), None);
+        assert_eq!(cube.try_get(Location::Corner(Up, Right, Front)), None);
+        assert_eq!(cube.try_get(Location::Edge(Up, Down)), None);
+        assert_eq!(cube.try_get(Location::Edge(Left, Left)), None);
+    }
+
+    #[test]
+    fn set_then_get() {
+        for location in Location::all() {
+            let mut cube = cube_with_moves("R2 U' L2");
+            for face in enum_iterator::all::<Face>() {
+                let face = match location {
+                    Location::Center(center) => center,
+                    _ => face,
+                };
+                cube.set(location, face);
+                assert_eq!(cube.get(location), face, "{:?}", location);
+            }
+        }
+    }
+
+    #[test]
+    fn set_only_changes_one_sticker() {
+        let mut cube = Cube::solved();
+        cube.set(
+            Location::Corner(Face::Up, Face::Back, Face::Left),
+            Face::Down,
+        );
+
+        let changed = Location::all()
+            .filter(|&l| cube.get(l) != Cube::solved().get(l))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changed,
+            vec![Location::Corner(Face::Up, Face::Back, Face::Left)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_center_panics() {
+        Cube::solved().set(Location::Center(Face::Up), Face::Down);
+    }
+
+    #[test]
+    fn display_solved() {
+        assert_eq!(
+            Cube::solved().to_string(),
+            [
+                "    UUU",
+                "    UUU",
+                "    UUU",
+                "LLL FFF RRR BBB ",
+                "LLL FFF RRR BBB ",
+                "LLL FFF RRR BBB ",
+                "    DDD",
+                "    DDD",
+                "    DDD",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn net_string_solved() {
+        assert_eq!(
+            Cube::solved().net_string(),
+            [
+                "   UUU      ",
+                "   UUU      ",
+                "   UUU      ",
+                "LLLFFFRRRBBB",
+                "LLLFFFRRRBBB",
+                "LLLFFFRRRBBB",
+                "   DDD      ",
+                "   DDD      ",
+                "   DDD      ",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[quickcheck]
+    fn net_string_is_fixed_width(cube: Cube) -> bool {
+        let net = cube.net_string();
+        net.lines().count() == 9 && net.lines().all(|line| line.len() == 12)
+    }
+
+    #[test]
+    fn net_string_has_display_stickers() {
+        let cube = cube_with_moves("R U F'");
+        let stickers = |s: String| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        assert_eq!(stickers(cube.net_string()), stickers(cube.to_string()));
+    }
+
+    #[test]
+    fn colored_is_display_with_escapes() {
+        let cube = cube_with_moves("R U F'");
+        let colored = cube.display_colored().to_string();
+        assert!(colored.contains("\x1b[30;48;5;196mR\x1b[0m"));
+
+        let stripped = colored
+            .split('\x1b')
+            .enumerate()
+            .map(|(i, part)| match i {
+                0 => part,
+                _ => part.split_once('m').unwrap().1,
+            })
+            .collect::<String>();
+        assert_eq!(stripped, cube.to_string());
+    }
+
+    #[test]
+    fn colored_uses_scheme_labels() {
+        let scheme = ColorScheme {
+            up: Sticker::new('W', (255, 255, 255)),
+            ..ColorScheme::default()
+        };
+        let colored = Cube::solved().display_with(scheme).to_string();
+        assert!(colored.contains("mW\x1b[0m"));
+        assert!(!colored.contains("mU\x1b[0m"));
+    }
+
+    #[test]
+    fn colored_uses_scheme_colors() {
+        let scheme = ColorScheme {
+            up: Sticker::new('W', (255, 0, 255)),
+            front: Sticker::new('G', ColorScheme::default().front.rgb),
+            ..ColorScheme::default()
+        };
+        let colored = Cube::solved().display_with(scheme).to_string();
+        assert!(colored.contains("\x1b[30;48;5;201mW\x1b[0m"));
+        assert!(colored.contains("\x1b[30;48;5;34mG\x1b[0m"));
+    }
+
+    #[test]
+    fn truecolor_uses_scheme_rgb() {
+        let cube = Cube::solved();
+        let colored = cube.display_colored().truecolor().to_string();
+        assert!(colored.contains("\x1b[30;48;2;183;18;52mR\x1b[0m"));
+        assert!(!colored.contains("48;5;"));
+    }
+
+    #[test]
+    fn solved_facelets() {
+        assert_eq!(
+            Cube::solved().to_facelets(&ColorScheme::default()),
+            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+        );
+    }
+
+    #[test]
+    fn facelets_after_r() {
+        assert_eq!(
+            cube_with_moves("R").to_facelets(&ColorScheme::default()),
+            "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"
+        );
+    }
+
+    #[quickcheck]
+    fn facelets_round_trip(moves: Vec<Move>) -> bool {
+        let scheme = ColorScheme {
+            front: Sticker::new('G', (0, 155, 72)),
+            ..ColorScheme::default()
+        };
+        let cube = Cube::solved().apply_all(moves);
+        Cube::from_facelets(&cube.to_facelets(&scheme), &scheme).unwrap() == cube
+    }
+
+    #[test]
+    fn from_facelets_rejects_bad_input() {
+        let scheme = ColorScheme::default();
+        let solved = Cube::solved().to_facelets(&scheme);
+        assert!(Cube::from_facelets(&solved[1..], &scheme).is_err());
+        assert!(Cube::from_facelets(&solved.replace('U', "X"), &scheme).is_err());
+        assert!(Cube::from_facelets(&solved.replacen("UUUUU", "UUUUR", 1), &scheme).is_err());
+    }
+
     #[test]
     fn two_cube_moves() {
-        let cube = Cube::solved().apply_all(Move::parse_sequence("F R2").unwrap());
+        let cube = Cube::solved().apply_all(moves![F, R2]);
         assert_eq!(
             cube.up,
             Surface([Up, Up, Right, Down, Down, Left, Left, Up])
@@ -0,0 +1,114 @@
+use super::surface::sticker_index;
+use crate::prelude::*;
+
+use std::fmt::Write;
+
+const STICKER: usize = 30;
+
+impl Cube {
+    /// Renders the same unfolded net as `Display` as an SVG, with one rect per sticker.
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with(&ColorScheme::default())
+    }
+
+    pub fn to_svg_with(&self, scheme: &ColorScheme) -> String {
+        let mut svg = String::new();
+        let (width, height) = (12 * STICKER, 9 * STICKER);
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height,
+        )
+        .unwrap();
+
+        for location in Location::all() {
+            let (x, y) = net_position(location);
+            writeln!(
+                svg,
+                r##"  <rect x="{}" y="{}" width="{s}" height="{s}" fill="{}" stroke="#000"/>"##,
+                x * STICKER,
+                y * STICKER,
+                scheme.get(self.get(location)).hex(),
+                s = STICKER,
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Column and row of the sticker in the net, in units of stickers.
+fn net_position(location: Location) -> (usize, usize) {
+    let (face, (col, row)) = match location {
+        Location::Center(f) => (f, (1, 1)),
+        Location::Edge(f, ..) | Location::Corner(f, ..) => (
+            f,
+            match sticker_index(location) {
+                0 => (0, 0),
+                1 => (1, 0),
+                2 => (2, 0),
+                3 => (2, 1),
+                4 => (2, 2),
+                5 => (1, 2),
+                6 => (0, 2),
+                7 => (0, 1),
+                _ => unreachable!(),
+            },
+        ),
+    };
+
+    let (face_col, face_row) = match face {
+        Face::Up => (1, 0),
+        Face::Left => (0, 1),
+        Face::Front => (1, 1),
+        Face::Right => (2, 1),
+        Face::Back => (3, 1),
+        Face::Down => (1, 2),
+    };
+
+    (face_col * 3 + col, face_row * 3 + row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_a_rect_per_sticker() {
+        let svg = cube_with_moves("R U F'").to_svg();
+        assert_eq!(svg.matches("<rect ").count(), 54);
+        assert_eq!(svg.matches("/>").count(), 54);
+    }
+
+    #[test]
+    fn is_a_single_svg_element() {
+        let svg = Cube::solved().to_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches('<').count(), svg.matches('>').count());
+    }
+
+    #[test]
+    fn stickers_do_not_overlap() {
+        let mut positions = Location::all().map(net_position).collect::<Vec<_>>();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), 54);
+    }
+
+    #[test]
+    fn matches_display_layout() {
+        let cube = cube_with_moves("R U F'");
+        let rows = cube.to_string();
+        let rows = rows.lines().collect::<Vec<_>>();
+        for location in Location::all() {
+            let (x, y) = net_position(location);
+            let column = x + x / 3;
+            let sticker = rows[y].chars().nth(column).unwrap();
+            assert_eq!(sticker.to_string(), cube.get(location).to_string());
+        }
+    }
+}
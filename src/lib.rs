@@ -0,0 +1,21 @@
+#![cfg_attr(test, allow(non_local_definitions))]
+
+#[cfg(test)]
+#[macro_use]
+extern crate quickcheck_macros;
+#[cfg(test)]
+#[macro_use]
+extern crate quickcheck_derive;
+
+pub mod algorithm;
+pub mod blast_machine_evaluator;
+pub mod challenge;
+pub mod cube;
+pub mod r#move;
+pub mod scramble;
+pub mod solver;
+
+#[cfg(test)]
+mod test;
+
+pub mod prelude;
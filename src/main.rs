@@ -1,60 +1,58 @@
-#[cfg(test)]
-#[macro_use]
-extern crate quickcheck_macros;
-#[cfg(test)]
-#[macro_use]
-extern crate quickcheck_derive;
+use blastcube::{blast_machine_evaluator::BlastMachineEvaluator, prelude::*, scramble, solver};
 
-mod blast_machine_evaluator;
-mod challenge;
-mod cube;
-mod r#move;
-mod solver;
-
-#[cfg(test)]
-mod test;
-
-mod prelude;
-use prelude::*;
-
-use std::time::Instant;
+use std::{
+    io::{BufRead, IsTerminal},
+    time::Instant,
+};
 
 fn main() -> anyhow::Result<()> {
     simple_logger::SimpleLogger::new().init().unwrap();
 
-    let scrambles = [
-        "R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2",
-        "R2 U' L2 R2 L' B",        // Small for profiling
-        "R2 U' L2 R2 B2 L' B D F", // Release profiling
-        "R2 U' L' R2 B2 F' L F2 U2 L' U' B D U2 L2 D2 U R' B F' L R F U R2 B' F2 L2 U' L",
-    ]
-    .into_iter()
-    .map(|s| Move::parse_sequence(s))
-    .collect::<Result<Vec<_>, _>>()?;
-
-    let scramble = &scrambles[0];
-    log::info!(
-        "scramble: {}",
-        scramble
-            .iter()
-            .map(Move::to_string)
-            .collect::<Vec<_>>()
-            .join(" ")
-    );
-    log::info!("scramble.len(): {:#?}", scramble.len());
+    let args = Args::parse(std::env::args().skip(1))?;
 
-    let cube = Cube::solved().apply_all(scramble.iter().cloned());
-    log::info!("initial cube:\n{}", cube);
+    let challenge = Challenge {
+        table_cache: Some(std::env::temp_dir().join("blastcube")),
+        ..Challenge::new(BlastMachineEvaluator::default())
+    };
 
-    let evaluator = blast_machine_evaluator::BlastMachineEvaluator;
-    // |seq: &[_]| Duration::from_millis(100) * (seq.len() as u32),
+    let evaluator = challenge.evaluator;
+    let solver = match args.solver {
+        SolverKind::Kociemba => solver::boxed::<_, solver::Kociemba<_>>(challenge),
+        SolverKind::Mitm => solver::boxed::<_, solver::Mitm<_>>(challenge),
+        SolverKind::Iddfs => solver::boxed::<_, solver::NaiveIddfs<_>>(challenge),
+        SolverKind::LayerByLayer => solver::boxed::<_, solver::LayerByLayer<_>>(challenge),
+    };
+    run(&*solver, evaluator, args.scramble)
+}
 
-    let challenge = Challenge {
-        inspection: Duration::default(),
-        evaluator: evaluator.clone(),
+fn run(
+    solver: &dyn DynSolver<BlastMachineEvaluator>,
+    evaluator: BlastMachineEvaluator,
+    scramble: Option<ScrambleArg>,
+) -> anyhow::Result<()> {
+    let scramble = match scramble {
+        Some(ScrambleArg::Moves(moves)) => moves,
+        Some(ScrambleArg::Random) => scramble::of_length(25, &mut rand::thread_rng()),
+        Some(ScrambleArg::Stdin) => return solve_stdin(solver, evaluator),
+        None if !std::io::stdin().is_terminal() => return solve_stdin(solver, evaluator),
+        None => {
+            let scrambles = [
+                "R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2",
+                "R2 U' L2 R2 L' B",        // Small for profiling
+                "R2 U' L2 R2 B2 L' B D F", // Release profiling
+                "R2 U' L' R2 B2 F' L F2 U2 L' U' B D U2 L2 D2 U R' B F' L R F U R2 B' F2 L2 U' L",
+            ]
+            .into_iter()
+            .map(Move::parse_sequence)
+            .collect::<Result<Vec<_>, _>>()?;
+            scrambles[0].clone()
+        }
     };
+    log::info!("scramble: {}", join(&scramble));
+    log::info!("scramble.len(): {:#?}", scramble.len());
 
-    let solver = std::sync::Arc::new(solver::Kociemba::init(challenge));
+    let cube = Cube::solved().apply_all(scramble.iter().cloned());
+    log::info!("initial cube:\n{}", cube.display_colored());
 
     let started_at = Instant::now();
     let mut result_cube = cube.clone();
@@ -70,10 +68,134 @@ fn main() -> anyhow::Result<()> {
     if result_cube == Cube::solved() {
         log::info!("Solved in {:?}", started_at.elapsed());
     } else {
-        log::info!("DNF in {:?}", started_at.elapsed());
-        log::info!("final cube:\n{}", result_cube);
+        log::info!(
+            "DNF in {:?}, {} stickers misplaced",
+            started_at.elapsed(),
+            result_cube.misplaced_stickers()
+        );
+        log::info!("final cube:\n{}", result_cube.display_colored());
     }
+    log::info!("Solution: {}", format_solution(&moves, 4));
     log::info!("Evaluator(moves) = {:?}", evaluator.eval(&moves));
+    log::info!("Length: {}", Algorithm::new(moves).metrics());
+
+    Ok(())
+}
+
+fn solve_stdin(
+    solver: &dyn DynSolver<BlastMachineEvaluator>,
+    evaluator: BlastMachineEvaluator,
+) -> anyhow::Result<()> {
+    for (i, line) in std::io::stdin().lock().lines().enumerate() {
+        let line_number = i + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let scramble = match Move::parse_sequence(line) {
+            Ok(s) => s,
+            Err(e) => {
+                log::error!("line {}: {}", line_number, e);
+                continue;
+            }
+        };
+
+        let cube = Cube::solved().apply_all(scramble);
+        let solution = solver.solve(cube.clone()).collect::<Vec<_>>();
+        let solved = cube.apply_all(solution.iter().cloned()) == Cube::solved();
+
+        println!(
+            "{}: {} ({}, {:?})",
+            line_number,
+            join(&solution),
+            if solved { "solved" } else { "DNF" },
+            evaluator.eval(&solution),
+        );
+    }
 
     Ok(())
 }
+
+fn join(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Args {
+    scramble: Option<ScrambleArg>,
+    solver: SolverKind,
+}
+
+enum ScrambleArg {
+    Moves(Vec<Move>),
+    Random,
+    Stdin,
+}
+
+enum SolverKind {
+    Kociemba,
+    Mitm,
+    Iddfs,
+    LayerByLayer,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+        let mut random = false;
+        let mut stdin = false;
+        let mut solver = SolverKind::Kociemba;
+        let mut moves = Vec::new();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--random" => random = true,
+                "--stdin" => stdin = true,
+                "--solver" => {
+                    let name = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--solver requires a value"))?;
+                    solver = name.parse()?;
+                }
+                flag if flag.starts_with("--") => anyhow::bail!("Unrecognized flag {}", flag),
+                scramble => moves.extend(
+                    scramble
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<anyhow::Result<Vec<Move>>>()?,
+                ),
+            }
+        }
+
+        let scramble = match (random, stdin, moves.is_empty()) {
+            (false, false, true) => None,
+            (false, false, false) => Some(ScrambleArg::Moves(moves)),
+            (true, false, true) => Some(ScrambleArg::Random),
+            (false, true, true) => Some(ScrambleArg::Stdin),
+            _ => anyhow::bail!("Expected at most one of --random, --stdin or a scramble"),
+        };
+
+        Ok(Args { scramble, solver })
+    }
+}
+
+impl core::str::FromStr for SolverKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<SolverKind> {
+        match s {
+            "kociemba" => Ok(SolverKind::Kociemba),
+            "mitm" => Ok(SolverKind::Mitm),
+            "iddfs" => Ok(SolverKind::Iddfs),
+            "lbl" => Ok(SolverKind::LayerByLayer),
+            _ => anyhow::bail!(
+                "Unrecognized solver {}, expected kociemba|mitm|iddfs|lbl",
+                s
+            ),
+        }
+    }
+}
@@ -1,15 +1,16 @@
 use crate::cube::*;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
-#[cfg_attr(test, derive(Arbitrary))]
 pub struct Move {
     pub face: Face,
     pub direction: Direction,
 }
 
+/// `Move(R2)`, telling moves apart from notation in debug output while staying short enough to
+/// read in a long sequence.
 impl core::fmt::Debug for Move {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}{}", self.face, self.direction)
+        write!(f, "Move({})", self)
     }
 }
 
@@ -21,6 +22,91 @@ pub enum Direction {
     Reverse,
 }
 
+/// Builds a `Vec<Move>` from notation, checked at compile time. Since `'` can't appear in Rust
+/// tokens, prime moves are written with a `p` suffix.
+///
+/// ```
+/// use blastcube::{moves, prelude::*};
+///
+/// assert_eq!(moves![R, U, R2, Fp], Move::parse_sequence("R U R2 F'").unwrap());
+/// ```
+#[macro_export]
+macro_rules! moves {
+    ($($m:tt),* $(,)?) => {
+        vec![$($crate::__single_move!($m)),*]
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __single_move {
+    (F) => {
+        $crate::__move!(Front, Single)
+    };
+    (F2) => {
+        $crate::__move!(Front, Double)
+    };
+    (Fp) => {
+        $crate::__move!(Front, Reverse)
+    };
+    (R) => {
+        $crate::__move!(Right, Single)
+    };
+    (R2) => {
+        $crate::__move!(Right, Double)
+    };
+    (Rp) => {
+        $crate::__move!(Right, Reverse)
+    };
+    (U) => {
+        $crate::__move!(Up, Single)
+    };
+    (U2) => {
+        $crate::__move!(Up, Double)
+    };
+    (Up) => {
+        $crate::__move!(Up, Reverse)
+    };
+    (L) => {
+        $crate::__move!(Left, Single)
+    };
+    (L2) => {
+        $crate::__move!(Left, Double)
+    };
+    (Lp) => {
+        $crate::__move!(Left, Reverse)
+    };
+    (B) => {
+        $crate::__move!(Back, Single)
+    };
+    (B2) => {
+        $crate::__move!(Back, Double)
+    };
+    (Bp) => {
+        $crate::__move!(Back, Reverse)
+    };
+    (D) => {
+        $crate::__move!(Down, Single)
+    };
+    (D2) => {
+        $crate::__move!(Down, Double)
+    };
+    (Dp) => {
+        $crate::__move!(Down, Reverse)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __move {
+    ($face:ident, $direction:ident) => {
+        $crate::r#move::Move {
+            face: $crate::cube::Face::$face,
+            direction: $crate::r#move::Direction::$direction,
+        }
+    };
+}
+
 impl Move {
     pub fn could_follow(&self, other: &Move) -> bool {
         if !Face::same_axis(self.face, other.face) {
@@ -37,18 +123,70 @@ impl Move {
         })
     }
 
-    pub fn inverse_seq(seq: &[Move]) -> Vec<Move> {
-        seq.into_iter().rev().map(|m| m.reverse()).collect()
+    /// Every sequence of `len` moves that `should_consider` accepts, generated lazily.
+    pub fn canonical_sequences(len: usize) -> impl Iterator<Item = Vec<Move>> {
+        let mut sequences: Box<dyn Iterator<Item = Vec<Move>>> =
+            Box::new(core::iter::once(Vec::new()));
+        for _ in 0..len {
+            sequences = Box::new(sequences.flat_map(|seq| {
+                Move::all().filter_map(move |m| {
+                    if !seq.last().is_none_or(|last| m.could_follow(last)) {
+                        return None;
+                    }
+                    let mut next = seq.clone();
+                    next.push(m);
+                    Some(next)
+                })
+            }));
+        }
+        sequences
+    }
+
+    /// The sequence that undoes `seq`, e.g. to set up a scramble's state from solved.
+    ///
+    /// ```
+    /// use blastcube::prelude::*;
+    ///
+    /// let scramble = Move::parse_sequence("R U F'").unwrap();
+    /// assert_eq!(
+    ///     Move::invert_sequence(&scramble),
+    ///     Move::parse_sequence("F U' R'").unwrap()
+    /// );
+    /// ```
+    pub fn invert_sequence(seq: &[Move]) -> Vec<Move> {
+        seq.iter().rev().map(|m| m.reverse()).collect()
     }
 
     pub fn parse_sequence(s: &str) -> anyhow::Result<Vec<Move>> {
         s.split(" ").map(|s| s.parse()).collect()
     }
 
-    pub fn all() -> impl Iterator<Item = Move> {
-        enum_iterator::all::<Face>().flat_map(|face| {
-            enum_iterator::all::<Direction>().map(move |direction| Move { face, direction })
-        })
+    /// Every move, ordered by face as `Face` declares them and then Single, Double, Reverse. The
+    /// order is stable, it is the order of `index`.
+    pub const ALL: [Move; 18] = {
+        use Direction::*;
+        use Face::*;
+
+        let faces = [Front, Back, Left, Right, Up, Down];
+        let directions = [Single, Double, Reverse];
+        let mut all = [Move {
+            face: Front,
+            direction: Single,
+        }; 18];
+        let mut i = 0;
+        while i < 18 {
+            all[i] = Move {
+                face: faces[i / 3],
+                direction: directions[i % 3],
+            };
+            i += 1;
+        }
+        all
+    };
+
+    /// Iterates `Move::ALL`.
+    pub fn all() -> impl Iterator<Item = Move> + Clone {
+        Move::ALL.into_iter()
     }
 
     pub fn reverse(&self) -> Move {
@@ -57,9 +195,60 @@ impl Move {
             direction: self.direction.reverse(),
         }
     }
+
+    /// The move's position in `Move::all`, a stable id in `0..18` for tables and serialization.
+    pub fn index(&self) -> usize {
+        let direction = match self.direction {
+            Direction::Single => 0,
+            Direction::Double => 1,
+            Direction::Reverse => 2,
+        };
+        self.face as usize * 3 + direction
+    }
+
+    /// The move with this `index`, `None` past the 18 moves.
+    pub fn from_index(index: usize) -> Option<Move> {
+        Move::ALL.get(index).copied()
+    }
+
+    /// How many times `seq` must be repeated to get back to where it started, at most 1260 on
+    /// the 3x3. The empty sequence has order 1.
+    pub fn order(seq: &[Move]) -> usize {
+        let mut cube = Cube::solved();
+        for order in 1..=MAX_ORDER {
+            cube = cube.apply_all(seq.iter().copied());
+            if cube == Cube::solved() {
+                return order;
+            }
+        }
+        unreachable!("no sequence has order above {}", MAX_ORDER)
+    }
 }
 
+/// The largest order of any element of the cube group.
+const MAX_ORDER: usize = 1260;
+
 impl Direction {
+    /// Turning one way and then `other`, as a single turn, or `None` if they cancel out.
+    ///
+    /// Counts Single as one quarter turn clockwise, Double as two and Reverse as three, so
+    /// `R R'` vanishes and `R R` is `R2`.
+    pub fn compose(self, other: Direction) -> Option<Direction> {
+        let quarter_turns = |d| match d {
+            Direction::Single => 1,
+            Direction::Double => 2,
+            Direction::Reverse => 3,
+        };
+
+        match (quarter_turns(self) + quarter_turns(other)) % 4 {
+            0 => None,
+            1 => Some(Direction::Single),
+            2 => Some(Direction::Double),
+            3 => Some(Direction::Reverse),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn reverse(self) -> Direction {
         match self {
             Direction::Single => Direction::Reverse,
@@ -69,6 +258,12 @@ impl Direction {
     }
 }
 
+impl From<(Face, Direction)> for Move {
+    fn from((face, direction): (Face, Direction)) -> Self {
+        Move { face, direction }
+    }
+}
+
 impl core::str::FromStr for Move {
     type Err = anyhow::Error;
 
@@ -100,12 +295,34 @@ impl core::str::FromStr for Move {
     }
 }
 
+impl core::str::FromStr for Direction {
+    type Err = anyhow::Error;
+
+    /// The suffix after a face in notation, empty for `Single`.
+    fn from_str(s: &str) -> anyhow::Result<Direction> {
+        match s {
+            "" => Ok(Direction::Single),
+            "'" => Ok(Direction::Reverse),
+            "2" => Ok(Direction::Double),
+            _ => Err(anyhow::anyhow!("Unrecognized direction {}", s)),
+        }
+    }
+}
+
 impl core::fmt::Display for Move {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "{}{}", self.face, self.direction)
     }
 }
 
+/// As its notation, like `"R'"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Move {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl core::fmt::Display for Direction {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(
@@ -119,3 +336,195 @@ impl core::fmt::Display for Direction {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quickcheck::Arbitrary;
+
+    #[test]
+    fn canonical_pairs() {
+        let pairs = Move::canonical_sequences(2).collect::<Vec<_>>();
+        // 12 moves on another axis after each move, and the 3 on the opposite face after those
+        // on the first face of each axis.
+        assert_eq!(pairs.len(), 18 * 12 + 3 * 3 * 3);
+        assert!(pairs.iter().all(|seq| Move::should_consider(seq)));
+    }
+
+    #[test]
+    fn canonical_sequences_are_all_considered() {
+        let all = Move::all()
+            .flat_map(|a| Move::all().flat_map(move |b| Move::all().map(move |c| vec![a, b, c])))
+            .filter(|seq| Move::should_consider(seq))
+            .collect::<Vec<_>>();
+        assert_eq!(Move::canonical_sequences(3).collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn no_moves_is_one_sequence() {
+        assert_eq!(
+            Move::canonical_sequences(0).collect::<Vec<_>>(),
+            vec![vec![]]
+        );
+    }
+
+    #[test]
+    fn moves_macro_covers_all_moves() {
+        assert_eq!(
+            moves![F, F2, Fp, B, B2, Bp, L, L2, Lp, R, R2, Rp, U, U2, Up, D, D2, Dp],
+            Move::all().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn direction_round_trips() {
+        for direction in enum_iterator::all::<Direction>() {
+            assert_eq!(
+                direction.to_string().parse::<Direction>().unwrap(),
+                direction
+            );
+        }
+    }
+
+    #[test]
+    fn debug_is_distinct_from_display() {
+        let r2: Move = "R2".parse().unwrap();
+        assert_eq!(r2.to_string(), "R2");
+        assert_eq!(format!("{:?}", r2), "Move(R2)");
+        assert_eq!(format!("{:?}", moves![U, Fp]), "[Move(U), Move(F')]");
+    }
+
+    #[test]
+    fn compose_table() {
+        use Direction::*;
+
+        let table = [
+            (Single, Single, Some(Double)),
+            (Single, Double, Some(Reverse)),
+            (Single, Reverse, None),
+            (Double, Single, Some(Reverse)),
+            (Double, Double, None),
+            (Double, Reverse, Some(Single)),
+            (Reverse, Single, None),
+            (Reverse, Double, Some(Single)),
+            (Reverse, Reverse, Some(Double)),
+        ];
+        for (a, b, composed) in table {
+            assert_eq!(a.compose(b), composed, "{:?} then {:?}", a, b);
+        }
+    }
+
+    #[quickcheck]
+    fn compose_matches_turning_twice(face: Face, a: Direction, b: Direction) -> bool {
+        let twice = Cube::solved()
+            .apply(Move { face, direction: a })
+            .apply(Move { face, direction: b });
+        let once = match a.compose(b) {
+            Some(direction) => Cube::solved().apply(Move { face, direction }),
+            None => Cube::solved(),
+        };
+        twice == once
+    }
+
+    #[quickcheck]
+    fn compose_with_reverse_cancels(direction: Direction) -> bool {
+        direction.compose(direction.reverse()).is_none()
+    }
+
+    #[test]
+    fn rejects_unknown_directions() {
+        assert!("3".parse::<Direction>().is_err());
+        assert!("''".parse::<Direction>().is_err());
+        assert!("R".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn all_is_faces_then_directions() {
+        let nested = enum_iterator::all::<Face>()
+            .flat_map(|face| {
+                enum_iterator::all::<Direction>().map(move |direction| Move { face, direction })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(Move::ALL.to_vec(), nested);
+        assert_eq!(Move::all().collect::<Vec<_>>(), nested);
+    }
+
+    #[test]
+    fn stable_indices() {
+        assert_eq!(moves![R][0].index(), 9);
+        assert_eq!(moves![Up][0].index(), 14);
+        assert_eq!(moves![F2][0].index(), 1);
+        assert_eq!(Move::from_index(18), None);
+    }
+
+    #[test]
+    fn index_is_position_in_all() {
+        for (i, m) in Move::all().enumerate() {
+            assert_eq!(m.index(), i);
+            assert_eq!(Move::from_index(i), Some(m));
+        }
+    }
+
+    #[quickcheck]
+    fn shrinks_to_simpler_moves(m: Move) -> bool {
+        let all = Move::all().collect::<Vec<_>>();
+        let index = all.iter().position(|&a| a == m).unwrap();
+        m.shrink().eq(all[..index].iter().copied())
+    }
+
+    #[test]
+    fn failing_sequence_shrinks_to_one_move() {
+        // Claims no sequence disturbs the UFR corner, the simplest counterexample is F.
+        let holds = |moves: &Vec<Move>| {
+            let location = Location::Corner(Face::Up, Face::Front, Face::Right);
+            Cube::solved().apply_all(moves.clone()).get(location) == Face::Up
+        };
+
+        // The same greedy search quickcheck does after a failure.
+        let mut failing = Move::parse_sequence("D2 L' B2 R U' D R2 B' L U2").unwrap();
+        assert!(!holds(&failing));
+        while let Some(smaller) = failing.shrink().find(|s| !holds(s)) {
+            failing = smaller;
+        }
+
+        assert_eq!(failing, moves![F]);
+    }
+
+    #[test]
+    fn orders() {
+        let order = |s: &str| Move::order(&Move::parse_sequence(s).unwrap());
+        assert_eq!(Move::order(&[]), 1);
+        assert_eq!(order("R"), 4);
+        assert_eq!(order("R2"), 2);
+        assert_eq!(order("R U"), 105);
+        assert_eq!(order("R U R' U'"), 6);
+        assert_eq!(order("R U2 D' B D'"), 1260);
+    }
+
+    #[test]
+    fn superflip_orders() {
+        let order = |s: &str| Move::order(&Move::parse_sequence(s).unwrap());
+        // The superflip, and the edge flipping conjugate of a commutator it can be built from.
+        assert_eq!(
+            order("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2"),
+            2
+        );
+        assert_eq!(order("F R U R' U' F'"), 6);
+    }
+
+    #[quickcheck]
+    fn order_returns_to_start(moves: Vec<Move>) -> bool {
+        let order = Move::order(&moves);
+        let repeated = moves.repeat(order);
+        Cube::solved().apply_all(repeated) == Cube::solved()
+    }
+
+    #[quickcheck]
+    fn invert_sequence_undoes(moves: Vec<Move>) -> bool {
+        Cube::solved()
+            .apply_all(moves.clone())
+            .apply_all(Move::invert_sequence(&moves))
+            == Cube::solved()
+    }
+}
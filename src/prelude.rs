@@ -1,8 +1,11 @@
+pub use crate::algorithm::*;
 pub use crate::challenge::*;
 pub use crate::cube::*;
 pub use crate::r#move::*;
 pub use crate::solver::*;
 
+pub use crate::moves;
+
 #[cfg(test)]
 pub use crate::test::*;
 
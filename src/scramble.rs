@@ -0,0 +1,136 @@
+use crate::prelude::*;
+
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashMap;
+
+/// `n` random moves, each one that `Move::could_follow` the move before, so none of them
+/// cancel or merge.
+pub fn of_length<R: Rng>(n: usize, rng: &mut R) -> Vec<Move> {
+    sample(n, rng, |_| 1).expect("Moves on another axis can follow any move")
+}
+
+/// Like `of_length`, but each move is picked in proportion to its weight among those that could
+/// follow the last. Moves missing from `weights` weigh nothing and never appear.
+///
+/// `None` if no weighted move can follow one already picked, as when only one face has weight or
+/// none do.
+pub fn weighted<R: Rng>(n: usize, weights: HashMap<Move, u32>, rng: &mut R) -> Option<Vec<Move>> {
+    sample(n, rng, |m| weights.get(&m).copied().unwrap_or(0))
+}
+
+/// Whether `solution` solves the cube `scramble` leaves.
+pub fn verify(scramble: &[Move], solution: &[Move]) -> bool {
+    check_solution(scramble, solution).is_ok()
+}
+
+/// Like `verify`, but with the cube `solution` leaves when it doesn't solve it.
+pub fn check_solution(scramble: &[Move], solution: &[Move]) -> Result<(), Cube> {
+    let cube = Cube::solved()
+        .apply_all(scramble.iter().copied())
+        .apply_all(solution.iter().copied());
+    if cube == Cube::solved() {
+        Ok(())
+    } else {
+        Err(cube)
+    }
+}
+
+fn sample<R: Rng>(n: usize, rng: &mut R, weight: impl Fn(Move) -> u32) -> Option<Vec<Move>> {
+    let mut scramble: Vec<Move> = Vec::with_capacity(n);
+    while scramble.len() < n {
+        let candidates = Move::all()
+            .filter(|m| scramble.last().is_none_or(|last| m.could_follow(last)))
+            .collect::<Vec<_>>();
+        let move_ = candidates.choose_weighted(rng, |&m| weight(m)).ok()?;
+        scramble.push(*move_);
+    }
+    Some(scramble)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[quickcheck]
+    fn has_requested_length(n: u8, seed: u64) -> bool {
+        of_length(n as usize, &mut StdRng::seed_from_u64(seed)).len() == n as usize
+    }
+
+    #[quickcheck]
+    fn never_cancels(n: u8, seed: u64) -> bool {
+        Move::should_consider(&of_length(n as usize, &mut StdRng::seed_from_u64(seed)))
+    }
+
+    #[test]
+    fn same_seed_same_scramble() {
+        let scramble = |seed| of_length(25, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(scramble(7), scramble(7));
+        assert_ne!(scramble(7), scramble(8));
+    }
+
+    fn seq(s: &str) -> Vec<Move> {
+        Move::parse_sequence(s).unwrap()
+    }
+
+    #[test]
+    fn verifies_solutions() {
+        assert!(verify(&seq("R U F' L2"), &seq("L2 F U' R'")));
+        assert!(verify(&[], &[]));
+    }
+
+    #[test]
+    fn off_by_one_move_is_not_a_solution() {
+        let scramble = seq("R U F' L2");
+        assert!(!verify(&scramble, &seq("L2 F U'")));
+        assert!(!verify(&scramble, &seq("L2 F U' R' R")));
+        assert_eq!(
+            check_solution(&scramble, &seq("L2 F U'")),
+            Err(cube_with_moves("R"))
+        );
+    }
+
+    #[test]
+    fn zero_weight_never_appears() {
+        let mut weights = Move::all().map(|m| (m, 1)).collect::<HashMap<_, _>>();
+        weights.insert(moves![R2][0], 0);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let scramble = weighted(25, weights.clone(), &mut rng).unwrap();
+            assert!(Move::should_consider(&scramble));
+            assert!(!scramble.contains(&moves![R2][0]), "{:?}", scramble);
+        }
+    }
+
+    #[test]
+    fn favours_heavy_moves() {
+        let weights = HashMap::from([(moves![R][0], 10), (moves![U][0], 10), (moves![F][0], 1)]);
+        let scramble = weighted(1000, weights, &mut StdRng::seed_from_u64(0)).unwrap();
+
+        let count = |m: Move| scramble.iter().filter(|&&s| s == m).count();
+        assert_eq!(
+            count(moves![R][0]) + count(moves![U][0]) + count(moves![F][0]),
+            1000
+        );
+        assert!(count(moves![F][0]) < count(moves![R][0]));
+    }
+
+    #[test]
+    fn one_face_cannot_fill_a_scramble() {
+        let weights = HashMap::from([(moves![R][0], 1)]);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(weighted(1, weights.clone(), &mut rng), Some(moves![R]));
+        assert_eq!(weighted(2, weights, &mut rng), None);
+    }
+
+    #[test]
+    fn no_weight_cannot_start_a_scramble() {
+        let weights = Move::all().map(|m| (m, 0)).collect::<HashMap<_, _>>();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(weighted(0, weights.clone(), &mut rng), Some(vec![]));
+        assert_eq!(weighted(1, weights, &mut rng), None);
+        assert_eq!(weighted(1, HashMap::new(), &mut rng), None);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::prelude::*;
+
+use std::collections::{HashMap, VecDeque};
+
+/// A shortest sequence, in half turns, solving the cross on `face`.
+///
+/// Searches with IDA*, bounded by the furthest of the four edges from its place on its own.
+pub fn solve_cross(cube: &Cube, face: Face) -> Vec<Move> {
+    let search = CrossSearch {
+        face,
+        distances: face
+            .adjacent()
+            .map(|side| edge_distances(Location::Edge(face, side))),
+    };
+
+    let mut path = Vec::new();
+    for bound in search.min_moves(cube).. {
+        if search.search(cube, bound, &mut path) {
+            return path;
+        }
+    }
+    unreachable!()
+}
+
+struct CrossSearch {
+    face: Face,
+    /// For each edge of the cross, the moves it takes from each location to its own.
+    distances: [HashMap<Location, u8>; 4],
+}
+
+impl CrossSearch {
+    fn min_moves(&self, cube: &Cube) -> u8 {
+        self.face
+            .adjacent()
+            .iter()
+            .zip(&self.distances)
+            .map(|(&side, distances)| distances[&find_edge(cube, self.face, side)])
+            .max()
+            .unwrap()
+    }
+
+    fn search(&self, cube: &Cube, remaining: u8, path: &mut Vec<Move>) -> bool {
+        if cube.cross_solved(self.face) {
+            return true;
+        }
+        if self.min_moves(cube) > remaining {
+            return false;
+        }
+
+        for move_ in Move::all() {
+            if !path.last().is_none_or(|last| move_.could_follow(last)) {
+                continue;
+            }
+            path.push(move_);
+            if self.search(&cube.clone().apply(move_), remaining - 1, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+}
+
+/// Where the edge with colors `a` and `b` is, as the location of its `a` sticker.
+fn find_edge(cube: &Cube, a: Face, b: Face) -> Location {
+    Location::all()
+        .find(|&l| match l {
+            Location::Edge(x, y) => cube.get(l) == a && cube.get(Location::Edge(y, x)) == b,
+            _ => false,
+        })
+        .unwrap()
+}
+
+/// The moves an edge sticker takes to reach `home` from each location, ignoring everything else.
+fn edge_distances(home: Location) -> HashMap<Location, u8> {
+    let mut distances = HashMap::from([(home, 0)]);
+    let mut queue = VecDeque::from([home]);
+    while let Some(location) = queue.pop_front() {
+        let distance = distances[&location];
+        let (a, b) = match location {
+            Location::Edge(a, b) => (a, b),
+            _ => unreachable!(),
+        };
+        for move_ in Move::all() {
+            let next = find_edge(&Cube::solved().apply(move_), a, b);
+            distances.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                distance + 1
+            });
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_needs_nothing() {
+        for face in enum_iterator::all::<Face>() {
+            assert_eq!(solve_cross(&Cube::solved(), face), vec![]);
+        }
+    }
+
+    #[test]
+    fn ignores_the_rest_of_the_cube() {
+        let cube = cube_with_moves("R U");
+        assert_eq!(
+            solve_cross(&cube, Face::Down),
+            Move::parse_sequence("R'").unwrap()
+        );
+    }
+
+    #[test]
+    fn every_edge_location_is_reachable() {
+        let distances = edge_distances(Location::Edge(Face::Down, Face::Front));
+        assert_eq!(distances.len(), 24);
+        assert_eq!(distances.values().max(), Some(&3));
+    }
+
+    #[test]
+    fn solves_main_scramble() {
+        let cube = cube_with_moves("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2");
+        for face in enum_iterator::all::<Face>() {
+            let solution = solve_cross(&cube, face);
+
+            // Every cross is solvable in 8 moves.
+            assert!(solution.len() <= 8, "{:?}", solution);
+            assert!(cube.clone().apply_all(solution).cross_solved(face));
+        }
+    }
+}
@@ -1,392 +1,606 @@
-use crate::cube::coord::CoordCube;
+use crate::cube::coord::{CoordCube, TableSize};
+use crate::cube::subgroup::domino_moves;
 use crate::prelude::*;
+use crate::solver::pattern_database::{OwnTables, SharedTables, TableSource};
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc::{channel, sync_channel, RecvTimeoutError, SendError},
+    Arc,
+};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// What a solve that ran out of time had done, see `Kociemba::solve_until`.
+///
+/// Only whole phases are kept, so the partial solution is the first phase's path to the domino
+/// group once that phase is done, and nothing before then.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialSolution {
+    pub moves: Vec<Move>,
+    /// The cube after `moves`, as held.
+    pub cube: Cube,
+}
 
-use core::{cmp::Ordering, hash::Hash};
-use std::{collections::HashMap, sync::mpsc::channel, sync::Arc};
+impl PartialSolution {
+    pub fn misplaced_stickers(&self) -> usize {
+        self.cube.misplaced_stickers()
+    }
+}
 
+/// Two phases, reaching the domino group and then solving within it.
 pub struct Kociemba<E: Evaluator> {
-    challenge: Challenge<E>,
-
-    to_domino: Phase,
-    post_domino: Phase,
+    phased: PhasedSolver<E>,
+    orientation: Orientation,
 }
 
 impl<E: Evaluator> Solver<E> for Kociemba<E> {
     fn init(challenge: Challenge<E>) -> Self {
-        CoordCube::init_table();
-
-        Kociemba {
-            to_domino: {
-                let moves = Move::all().collect::<Vec<_>>();
-                let heuristics: Vec<Box<dyn Heuristic>> = vec![
-                    Box::new(HeuristicTable::init(
-                        "corner_orientation",
-                        |c| c.corner_orientation(),
-                        &moves,
-                        &challenge.evaluator,
-                        None,
-                    )),
-                    Box::new(HeuristicTable::init(
-                        "edge_orientation",
-                        |c| c.edge_orientation(),
-                        &moves,
-                        &challenge.evaluator,
-                        None,
-                    )),
-                ];
-                Phase::init(moves, is_domino_cube, heuristics)
-            },
-            post_domino: {
-                let moves = domino_moves().collect::<Vec<_>>();
-                let heuristics: Vec<Box<dyn Heuristic>> = vec![Box::new(HeuristicTable::init(
-                    "corner_position",
-                    |c| c.corner_position(),
-                    &moves,
-                    &challenge.evaluator,
-                    Some(Duration::from_millis(3000)),
-                ))];
-                Phase::init(moves, |c| *c == Cube::solved(), heuristics)
-            },
-
-            challenge,
-        }
+        CoordCube::init_table_logging_at(challenge.log_level());
+        Kociemba::with_tables(challenge, OwnTables)
     }
 
     fn solve(self: &Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
-        let (tx, rx) = channel();
-
-        let this = Arc::clone(self);
-        let before_spawn = std::time::Instant::now();
-        std::thread::spawn(move || {
-            log::info!("Took {:?} to spawn worker thread", before_spawn.elapsed());
-            let to_domino = this.solve_to(&cube, &this.to_domino, Vec::new());
-            let domino_len = to_domino.len();
-            for m in &to_domino {
-                tx.send(*m).unwrap();
+        let cube = cube.held(self.orientation);
+        match self.phased.challenge().channel_bound {
+            None => {
+                let (tx, rx) = channel();
+                self.spawn_worker(cube, Arc::default(), move |m| tx.send(m), |_| {});
+                Box::new(rx.into_iter())
             }
-            log::info!("Domino path: {:?}", to_domino);
-
-            let solution = this.solve_to(&cube, &this.post_domino, to_domino);
-            for m in &solution[domino_len..] {
-                tx.send(*m).unwrap();
+            Some(bound) => {
+                let (tx, rx) = sync_channel(bound);
+                self.spawn_worker(cube, Arc::default(), move |m| tx.send(m), |_| {});
+                Box::new(rx.into_iter())
             }
-        });
+        }
+    }
 
-        Box::new(rx.into_iter())
+    fn solve_blocking(self: &Arc<Self>, cube: Cube) -> Vec<Move> {
+        self.solve_held(&cube)
     }
 }
 
-impl<E: Evaluator> Kociemba<E> {
-    fn solve_to(&self, cube: &Cube, phase: &Phase, mut prefix: Vec<Move>) -> Vec<Move> {
-        let cube = CoordCube::from(cube.clone().apply_all(prefix.clone()));
-
-        let mut best_time = self.challenge.evaluator.eval(&prefix);
-        loop {
-            log::info!("Searching <= {:?}", best_time);
-            match self.find_solution(best_time, &cube, &mut prefix, phase) {
-                Search::Found(moves) => return moves,
-                Search::NotFound(next_best_time) => {
-                    best_time = next_best_time;
-                }
-            }
+impl<E: Evaluator + PartialEq + Clone> Kociemba<E> {
+    /// Build every table the solver needs, transition tables and heuristic tables alike, so
+    /// the first solve doesn't wait on any. `progress` is called as the transition tables are
+    /// built, as for `CoordCube::init_table_with_progress`, and then once for each heuristic
+    /// table when it is ready, with its size and nothing left to expand.
+    ///
+    /// The transition tables are only ever built once. Heuristic tables are shared with any
+    /// other precomputed solver for an equal evaluator that is still alive, so calling this
+    /// again while keeping the first is cheap, with or without a `table_cache`.
+    pub fn precompute(challenge: Challenge<E>, progress: impl Fn(&str, usize, usize)) -> Self {
+        CoordCube::init_table_with_progress(&progress);
+        let kociemba = Kociemba::with_tables(challenge, SharedTables);
+        for size in kociemba.phased.table_sizes() {
+            progress(&size.name, size.states, 0);
         }
+        kociemba
     }
+}
 
-    fn find_solution(
-        &self,
-        max_time: Duration,
-        cube: &CoordCube,
-        move_stack: &mut Vec<Move>,
-        phase: &Phase,
-    ) -> Search {
-        let min_time = phase.min_time(cube);
-        let this_time = self.challenge.evaluator.eval(move_stack) + min_time;
-        if this_time > max_time {
-            return Search::NotFound(this_time);
+impl<E: Evaluator> Kociemba<E> {
+    /// Panics in debug builds if the evaluator is caught breaking the subadditivity the
+    /// heuristics rely on.
+    fn with_tables(challenge: Challenge<E>, tables: impl TableSource<E>) -> Self {
+        if cfg!(debug_assertions) {
+            if let Err(e) = challenge.evaluator.check_subadditive(1000) {
+                panic!("{}", e);
+            }
         }
 
-        if min_time == Duration::default() && phase.is_finished(&cube.raw) {
-            return Search::Found(move_stack.clone());
-        }
+        let to_domino = {
+            let moves = Move::all().collect::<Vec<_>>();
+            let heuristics: Vec<Box<dyn Heuristic>> = vec![
+                Box::new(tables.table(
+                    "corner_orientation",
+                    |c| c.corner_orientation(),
+                    &moves,
+                    &challenge,
+                    None,
+                )),
+                Box::new(tables.table(
+                    "edge_orientation",
+                    |c| c.edge_orientation(),
+                    &moves,
+                    &challenge,
+                    None,
+                )),
+            ];
+            Phase::init(moves, CoordCube::is_domino, heuristics)
+        };
+        let post_domino = {
+            let moves = domino_moves().collect::<Vec<_>>();
+            let heuristics: Vec<Box<dyn Heuristic>> = vec![Box::new(tables.table(
+                "corner_position",
+                |c| c.corner_position(),
+                &moves,
+                &challenge,
+                Some(TableLimit::Depth(9)),
+            ))];
+            Phase::init(moves, CoordCube::is_solved, heuristics)
+        };
 
-        let last_move = move_stack.last().cloned();
-        phase
-            .allowed_moves
-            .iter()
-            .filter(|move_| match last_move {
-                None => true,
-                Some(m) => move_.could_follow(&m),
-            })
-            .fold(Search::NotFound(Duration::MAX), |best, &move_| {
-                move_stack.push(move_);
-                let cube = cube.clone().apply(move_);
-                let sub = self.find_solution(max_time, &cube, move_stack, phase);
-                move_stack.pop();
-
-                match (best, sub) {
-                    (Search::NotFound(a), Search::NotFound(b)) => {
-                        Search::NotFound(core::cmp::min(a, b))
-                    }
-                    (Search::Found(m), Search::NotFound(_)) => Search::Found(m),
-                    (Search::NotFound(_), Search::Found(m)) => Search::Found(m),
-                    (Search::Found(a), Search::Found(b)) => {
-                        let a_time = self.challenge.evaluator.eval(&a);
-                        let b_time = self.challenge.evaluator.eval(&b);
-                        Search::Found(match a_time.cmp(&b_time) {
-                            Ordering::Less | Ordering::Equal => a,
-                            Ordering::Greater => b,
-                        })
-                    }
-                }
-            })
-            .into()
+        Kociemba {
+            phased: PhasedSolver::new(challenge, vec![to_domino, post_domino]),
+            orientation: Orientation::default(),
+        }
     }
-}
 
-enum Search {
-    NotFound(Duration),
-    Found(Vec<Move>),
-}
+    /// Finish the `count` cheapest `to_domino` solutions instead of just the first, keeping the
+    /// cheapest overall. The first phase's moves aren't sent until all of them are finished.
+    pub fn phase1_candidates(mut self, count: usize) -> Self {
+        self.phased = self.phased.first_phase_candidates(count);
+        self
+    }
 
-fn domino_moves() -> impl Iterator<Item = Move> {
-    Move::all().filter(is_domino_move)
-}
+    /// Solve cubes held in `orientation`, as a robot with a fixed mount holds them.
+    ///
+    /// Cubes are still given by their own faces, but moves name the faces of the holder's frame
+    /// instead, so `U` turns whichever face points up. The move set is the same 18 turns of that
+    /// frame, and the goal is each face matching the center it's held with, which is the solved
+    /// cube once relabelled by `Cube::held`.
+    pub fn held(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
 
-fn is_domino_move(m: &Move) -> bool {
-    match (m.face, m.direction) {
-        (Face::Up | Face::Down, _) => true,
-        (_, Direction::Double) => true,
-        _ => false,
+    /// The sizes of the transition tables and then of each phase's heuristic tables.
+    pub fn table_sizes(&self) -> Vec<TableSize> {
+        let mut sizes = CoordCube::table_sizes();
+        sizes.extend(self.phased.table_sizes());
+        sizes
     }
-}
 
-fn is_domino_cube(cube: &Cube) -> bool {
-    use Face::*;
+    /// Solve on the calling thread, along with whether the solution is proven optimal. See
+    /// `PhasedSolver::quality` for when it can be.
+    pub fn solve_with_quality(&self, cube: Cube) -> (Vec<Move>, SolutionQuality) {
+        let cube = cube.held(self.orientation);
+        let mut moves = Vec::new();
+        self.phased
+            .solve_with(&cube, |m| moves.push(m))
+            .expect("Both phases can finish from any solvable cube");
+        let quality = self.phased.quality(&cube, &moves);
+        (moves, quality)
+    }
 
-    Location::all().all(|l| match (l, cube.get(l)) {
-        (Location::Center(_), _) => true,
+    /// Solve on the calling thread, along with where the domino phase ends. The first boundary
+    /// is the length of the path to the domino group, the second the whole solution's.
+    pub fn solve_phased(&self, cube: Cube) -> PhasedSolution {
+        self.phased
+            .solve_phased(&cube.held(self.orientation))
+            .expect("Both phases can finish from any solvable cube")
+    }
 
-        (Location::Edge(Up | Down, _), Up | Down) => true,
-        (Location::Corner(Up | Down, _, _), Up | Down) => true,
+    /// The fewest moves of the holder's frame into the domino group, however long the
+    /// evaluator says they take. See `CoordCube::domino_distance`.
+    pub fn domino_distance(&self, cube: &Cube) -> usize {
+        CoordCube::from(cube.held(self.orientation)).domino_distance()
+    }
 
-        (Location::Edge(Front | Back, Left | Right), Front | Back) => true,
+    /// Solve every cube, sharing the tables across one thread per available core. Solutions are
+    /// in the same order as `cubes`.
+    pub fn solve_all(&self, cubes: Vec<Cube>) -> Vec<Vec<Move>> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let next = AtomicUsize::new(0);
+
+        let mut solutions = std::thread::scope(|s| {
+            let workers = (0..threads.min(cubes.len()))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut solved = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(cube) = cubes.get(i) else {
+                                return solved;
+                            };
+                            solved.push((i, self.solve_held(cube)));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect::<Vec<_>>()
+        });
 
-        (Location::Edge(Front | Back, _), _) => true,
-        (Location::Edge(Left | Right, _), _) => true,
-        (Location::Corner(Front | Back | Left | Right, _, _), _) => true,
+        solutions.sort_by_key(|&(i, _)| i);
+        solutions.into_iter().map(|(_, moves)| moves).collect()
+    }
 
-        _ => false,
-    })
-}
+    /// Like `Solver::solve`, but with each move the cube as it is after that move, as held.
+    /// The worker keeps the running cube, so consumers can render it without replaying moves.
+    pub fn solve_states(self: &Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = (Move, Cube)>> {
+        let cube = cube.held(self.orientation);
+        let mut state = cube.clone();
+        let mut step = move |m: Move| {
+            state.rotate(m);
+            (m, state.clone())
+        };
+        match self.phased.challenge().channel_bound {
+            None => {
+                let (tx, rx) = channel();
+                self.spawn_worker(cube, Arc::default(), move |m| tx.send(step(m)), |_| {});
+                Box::new(rx.into_iter())
+            }
+            Some(bound) => {
+                let (tx, rx) = sync_channel(bound);
+                self.spawn_worker(cube, Arc::default(), move |m| tx.send(step(m)), |_| {});
+                Box::new(rx.into_iter())
+            }
+        }
+    }
 
-struct Phase {
-    allowed_moves: Vec<Move>,
-    finished_when: fn(&Cube) -> bool,
-    heuristics: Vec<Box<dyn Heuristic>>,
-}
+    /// Solve, giving up at `deadline` with the phases finished by then.
+    ///
+    /// Moves arrive as they are found, so at the deadline they may end partway through a phase.
+    /// Those are dropped, back to where the last finished phase ended. The search is cancelled
+    /// rather than left running in the background. A panic in the search is raised again here.
+    pub fn solve_until(
+        self: &Arc<Self>,
+        cube: Cube,
+        deadline: Instant,
+    ) -> Result<Vec<Move>, PartialSolution> {
+        let cube = cube.held(self.orientation);
+        let (tx, rx) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicUsize::new(0));
+        let worker = {
+            let finished = Arc::clone(&finished);
+            self.spawn_worker(
+                cube.clone(),
+                Arc::clone(&cancel),
+                move |m| tx.send(m),
+                move |end| finished.store(end, Ordering::Release),
+            )
+        };
 
-impl Phase {
-    fn init(
-        allowed_moves: impl IntoIterator<Item = Move>,
-        finished_when: fn(&Cube) -> bool,
-        heuristics: Vec<Box<dyn Heuristic>>,
-    ) -> Self {
-        Self {
-            allowed_moves: allowed_moves.into_iter().collect(),
-            finished_when,
-            heuristics,
+        let mut moves = Vec::new();
+        loop {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(m) => moves.push(m),
+                Err(RecvTimeoutError::Disconnected) => {
+                    if let Err(panic) = worker.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                    return Ok(moves);
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    cancel.store(true, Ordering::Relaxed);
+                    // The finished phases' moves were all sent, but may not have arrived yet.
+                    let finished = finished.load(Ordering::Acquire);
+                    moves.extend(rx.try_iter().take(finished.saturating_sub(moves.len())));
+                    moves.truncate(finished);
+                    let cube = cube.apply_all(moves.iter().copied());
+                    return Err(PartialSolution { moves, cube });
+                }
+            }
         }
     }
 
-    fn min_time(&self, cube: &CoordCube) -> Duration {
-        self.heuristics
-            .iter()
-            .map(|h| h.min_time(cube))
-            .max()
-            .unwrap_or_default()
+    fn solve_held(&self, cube: &Cube) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.phased
+            .solve_with(&cube.held(self.orientation), |m| moves.push(m))
+            .expect("Both phases can finish from any solvable cube");
+        moves
     }
 
-    fn is_finished(&self, cube: &Cube) -> bool {
-        (self.finished_when)(cube)
+    /// Solve on a new thread, sending each move as it is found and calling `phase_done` as for
+    /// `PhasedSolver::try_solve_cancellable`. The worker stops once a send fails, which happens
+    /// when the receiver is dropped, or once `cancel` is set.
+    fn spawn_worker<T: Send + 'static>(
+        self: &Arc<Self>,
+        cube: Cube,
+        cancel: Arc<AtomicBool>,
+        send: impl FnMut(Move) -> Result<(), SendError<T>> + Send + 'static,
+        phase_done: impl FnMut(usize) + Send + 'static,
+    ) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        let before_spawn = std::time::Instant::now();
+        std::thread::spawn(move || {
+            log::debug!("Took {:?} to spawn worker thread", before_spawn.elapsed());
+            match this
+                .phased
+                .try_solve_cancellable(&cube, &cancel, send, phase_done)
+            {
+                Ok(result) => result.expect("Both phases can finish from any solvable cube"),
+                Err(_) => log::debug!("Solution receiver dropped, stopping"),
+            }
+        })
     }
 }
 
-trait Heuristic: Sync + Send {
-    fn min_time(&self, cube: &CoordCube) -> Duration;
-}
-
-struct HeuristicTable<T: Eq + Hash, F> {
-    name: String,
-    exhaustive: bool,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+    use crate::solver::pattern_database::BUILDS;
+    use crate::solver::phased::NODES;
 
-    map: HashMap<T, Duration>,
-    simplifier: F,
-}
+    fn challenge() -> Challenge<BlastMachineEvaluator> {
+        Challenge::new(BlastMachineEvaluator::default())
+    }
 
-impl<T: Eq + Hash + core::fmt::Debug, F> HeuristicTable<T, F>
-where
-    F: Fn(&CoordCube) -> T,
-{
-    fn init(
-        name: &str,
-        simplifier: F,
-        allowed_moves: &[Move],
-        evaluator: &impl Evaluator,
-        max_setup: Option<Duration>,
-    ) -> Self {
-        let mut result = Self {
-            name: name.to_string(),
-            exhaustive: true,
-
-            simplifier,
-            map: HashMap::default(),
-        };
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "evaluator isn't subadditive")]
+    fn rejects_super_linear_evaluator() {
+        let squared = |seq: &[Move]| Duration::from_millis(1) * (seq.len() * seq.len()) as u32;
+        Kociemba::init(Challenge::new(squared));
+    }
 
-        let start = std::time::Instant::now();
-        for depth in 0..21 {
-            log::info!(
-                "{}: Expanding to depth: {}, {} items",
-                result.name,
-                depth,
-                result.map.len()
-            );
-            let should_break = match max_setup {
-                Some(max) if start.elapsed() >= max => {
-                    result.exhaustive = false;
-                    true
-                }
-                _ => !result.expand_to_depth(depth, &mut Vec::new(), evaluator, allowed_moves),
-            };
-            if should_break {
-                log::info!(
-                    "{}: Finished expanding at depth {}, {} items, took {:?}",
-                    result.name,
-                    depth,
-                    result.map.len(),
-                    start.elapsed(),
-                );
-                break;
-            }
-        }
+    #[test]
+    fn precompute_again_is_cheap() {
+        let first = Kociemba::precompute(challenge(), |_, _, _| {});
 
-        result
+        let reported = core::cell::RefCell::new(Vec::new());
+        let builds = BUILDS.with(|b| b.get());
+        let again = Kociemba::precompute(challenge(), |name, states, left| {
+            reported.borrow_mut().push((name.to_string(), states, left));
+        });
+        assert_eq!(BUILDS.with(|b| b.get()), builds, "Every table is reused");
+
+        // Only the heuristic tables are reported, the transition tables were already built.
+        let heuristic_sizes = again
+            .phased
+            .table_sizes()
+            .into_iter()
+            .map(|size| (size.name, size.states, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(reported.into_inner(), heuristic_sizes);
+        assert_eq!(again.table_sizes(), first.table_sizes());
     }
 
-    fn expand_to_depth(
-        &mut self,
-        depth: usize,
-        move_stack: &mut Vec<Move>,
-        evaluator: &impl Evaluator,
-        allowed_moves: &[Move],
-    ) -> bool {
-        let inv = Move::inverse_seq(move_stack);
-        if !Move::should_consider(&inv) {
-            return false;
+    #[test]
+    fn table_sizes_include_heuristics() {
+        let sizes = KOCIEMBA.table_sizes();
+        let names = sizes.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "corner_orientation",
+                "edge_orientation",
+                "corner_position",
+                "corner_orientation",
+                "edge_orientation",
+                "corner_position",
+            ]
+        );
+
+        // Each heuristic has at most the states its coordinate can take.
+        for (heuristic, transitions) in sizes[3..].iter().zip(&sizes) {
+            assert!(heuristic.states <= transitions.states, "{:?}", heuristic);
         }
+    }
 
-        let value = self.simplify_depr(&Cube::solved().apply_all(move_stack.clone()));
-        let time = evaluator.min_time(&inv);
+    #[test]
+    fn blocking_matches_threaded() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
 
-        let already = self.map.get(&value);
-        match (depth, already) {
-            (0, None) => {
-                self.map.insert(value, time);
-                true
+        let threaded = KOCIEMBA.solve(cube.clone()).collect::<Vec<_>>();
+        let blocking = KOCIEMBA.solve_blocking(cube.clone());
+
+        assert_eq!(blocking, threaded);
+        assert_eq!(cube.apply_all(blocking), Cube::solved());
+    }
+
+    fn oriented_edges(channel_bound: Option<usize>) -> Arc<Kociemba<BlastMachineEvaluator>> {
+        let challenge = Challenge {
+            channel_bound,
+            ..Challenge::new(BlastMachineEvaluator::default())
+        };
+        let oriented = |c: &CoordCube| c.edge_orientation() == 0;
+        Arc::new(Kociemba {
+            phased: PhasedSolver::new(challenge, vec![Phase::init(Move::all(), oriented, vec![])]),
+            orientation: Orientation::default(),
+        })
+    }
+
+    #[test]
+    fn moves_are_in_the_held_frame() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let phase = Phase::init(Move::all(), CoordCube::is_solved, vec![]);
+        let upside_down = Arc::new(
+            Kociemba {
+                phased: PhasedSolver::new(challenge, vec![phase]),
+                orientation: Orientation::default(),
             }
-            (0, Some(t)) if time < *t => {
-                self.map.insert(value, time);
-                true
+            .held(Orientation::new(Face::Down, Face::Front).unwrap()),
+        );
+
+        let cube = cube_with_moves("R U");
+        let solution = upside_down.solve_blocking(cube.clone());
+
+        assert_eq!(solution, moves![Dp, Lp]);
+        assert_eq!(
+            upside_down.solve(cube.clone()).collect::<Vec<_>>(),
+            solution
+        );
+        assert_eq!(
+            cube.held(upside_down.orientation).apply_all(solution),
+            Cube::solved()
+        );
+    }
+
+    #[test]
+    fn solve_states_ends_solved() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+        let steps = KOCIEMBA.solve_states(cube.clone()).collect::<Vec<_>>();
+
+        let moves = steps.iter().map(|&(m, _)| m).collect::<Vec<_>>();
+        assert_eq!(moves, KOCIEMBA.solve_blocking(cube.clone()));
+
+        let states = steps.into_iter().map(|(_, c)| c).collect::<Vec<_>>();
+        assert_eq!(states, cube.scan(moves).collect::<Vec<_>>());
+        assert_eq!(states.last(), Some(&Cube::solved()));
+    }
+
+    #[test]
+    fn solve_states_are_held() {
+        let challenge = Challenge {
+            channel_bound: Some(0),
+            ..Challenge::new(BlastMachineEvaluator::default())
+        };
+        let phase = Phase::init(Move::all(), CoordCube::is_solved, vec![]);
+        let upside_down = Orientation::new(Face::Down, Face::Front).unwrap();
+        let solver = Arc::new(
+            Kociemba {
+                phased: PhasedSolver::new(challenge, vec![phase]),
+                orientation: Orientation::default(),
             }
-            (0, Some(_)) => false,
-
-            (_, Some(t)) if *t < time => false,
-            (_, Some(_)) => allowed_moves.iter().fold(false, |any, move_| {
-                move_stack.push(*move_);
-                let result = self.expand_to_depth(depth - 1, move_stack, evaluator, allowed_moves);
-                move_stack.pop();
-                any || result
-            }),
-            (_, None) => unreachable!(),
-        }
+            .held(upside_down),
+        );
+
+        let cube = cube_with_moves("R U");
+        let steps = solver.solve_states(cube.clone()).collect::<Vec<_>>();
+
+        assert_eq!(
+            steps,
+            vec![
+                (moves![Dp][0], cube.held(upside_down).apply_all(moves![Dp])),
+                (moves![Lp][0], Cube::solved()),
+            ]
+        );
     }
 
-    #[cfg(test)]
-    fn has(&self, cube: &Cube) -> bool {
-        let simplified = self.simplify_depr(cube);
-        self.map.contains_key(&simplified)
+    #[test]
+    fn rendezvous_matches_unbounded() {
+        let cube = cube_with_moves("R U F B'");
+        assert_eq!(
+            oriented_edges(Some(0))
+                .solve(cube.clone())
+                .collect::<Vec<_>>(),
+            oriented_edges(None).solve(cube).collect::<Vec<_>>(),
+        );
     }
 
-    fn simplify(&self, cube: &CoordCube) -> T {
-        (self.simplifier)(cube)
+    #[test]
+    fn stops_at_first_solution() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+
+        NODES.with(|n| n.set(0));
+        let solution = KOCIEMBA.solve_blocking(cube.clone());
+        // Comparing every sibling's solution at each bound visited 38683 nodes.
+        assert!(NODES.with(|n| n.get()) < 38_683);
+
+        // Nine moves, but equally fast solutions found first would be just as good.
+        assert!(solution.len() <= 12, "{:?}", solution);
+        assert_eq!(
+            cube.clone().apply_all(solution.iter().copied()),
+            Cube::solved()
+        );
+        assert_eq!(KOCIEMBA.solve(cube).collect::<Vec<_>>(), solution);
     }
 
-    fn simplify_depr(&self, cube: &Cube) -> T {
-        (self.simplifier)(&CoordCube::from(cube.clone()))
+    #[test]
+    fn more_phase1_candidates_can_be_faster() {
+        let cube = cube_with_moves("R2 U' L2 R2 F");
+        let best_of_four = Arc::new(Kociemba::init(challenge()).phase1_candidates(4));
+
+        let first = KOCIEMBA.solve_blocking(cube.clone());
+        let fastest = best_of_four.solve_blocking(cube.clone());
+
+        let eval = |moves: &[Move]| BlastMachineEvaluator::default().eval(moves);
+        assert!(eval(&fastest) < eval(&first));
+        assert_eq!(cube.apply_all(fastest), Cube::solved());
     }
-}
 
-impl<T, F> Heuristic for HeuristicTable<T, F>
-where
-    T: Eq + Hash + Sync + Send + core::fmt::Debug,
-    F: Fn(&CoordCube) -> T + Sync + Send,
-{
-    fn min_time(&self, cube: &CoordCube) -> Duration {
-        let value = self.simplify(cube);
-        if let Some(d) = self.map.get(&value) {
-            return *d;
-        }
+    #[test]
+    fn domino_boundary_is_domino() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+        let solution = KOCIEMBA.solve_phased(cube.clone());
+        assert_eq!(solution.moves, KOCIEMBA.solve_blocking(cube.clone()));
 
-        if self.exhaustive {
-            panic!(
-                "{}: missing value ({:?}) for cube\n{:?}",
-                self.name, value, cube
-            );
-        }
-        Duration::default()
+        let [domino_len, len] = solution.phase_boundaries[..] else {
+            panic!("{:?}", solution.phase_boundaries);
+        };
+        assert_eq!(len, solution.moves.len());
+        let domino = cube.apply_all(solution.moves[..domino_len].iter().copied());
+        assert!(CoordCube::from(domino).is_domino());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn domino_phase_is_at_least_domino_distance() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B F' D");
+        let solution = KOCIEMBA.solve_phased(cube.clone());
+        let distance = KOCIEMBA.domino_distance(&cube);
+        assert!(distance > 0);
+        assert!(solution.phase_boundaries[0] >= distance);
+    }
+
+    #[test]
+    fn domino_cube_has_empty_first_phase() {
+        let solution = KOCIEMBA.solve_phased(cube_with_moves("U R2 D'"));
+        assert_eq!(solution.phase_boundaries, vec![0, solution.moves.len()]);
+    }
 
-    #[cfg(test)]
-    mod heuristic_table {
-        use super::*;
+    #[test]
+    fn solve_until_in_time_solves() {
+        let cube = cube_with_moves("R2 U' L2 R2 L' B");
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let solution = KOCIEMBA.solve_until(cube.clone(), deadline).unwrap();
+        assert_eq!(cube.apply_all(solution), Cube::solved());
+    }
 
-        fn simple_evaluator(moves: &[Move]) -> Duration {
-            Duration::from_millis(10) * (moves.len() as u32)
-        }
+    #[test]
+    fn solve_until_past_deadline_is_partial() {
+        let cube = cube_with_moves("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2");
+        let partial = KOCIEMBA
+            .solve_until(cube.clone(), Instant::now())
+            .unwrap_err();
+
+        let reached = cube.apply_all(partial.moves.iter().copied());
+        assert_eq!(partial.cube, reached);
+        assert!(partial.misplaced_stickers() > 0);
+        assert!(partial.moves.is_empty() || CoordCube::from(reached).is_domino());
+    }
 
-        lazy_static::lazy_static! {
-            static ref CORNER_ORIENTATION:
-                    HeuristicTable<u16, Box<dyn Fn(&CoordCube) -> u16 + Sync + Send>>
-            = HeuristicTable::init(
-                "corner_orientation",
-                Box::new(|c| c.corner_orientation()),
-                &Move::all().collect::<Vec<_>>(),
-                &simple_evaluator,
-                None,
-            );
-        }
+    #[test]
+    #[should_panic(expected = "Both phases can finish")]
+    fn solve_until_raises_worker_panic() {
+        let up_down = Move::all().filter(|m| matches!(m.face, Face::Up | Face::Down));
+        let kociemba = Arc::new(Kociemba {
+            phased: PhasedSolver::new(
+                challenge(),
+                vec![
+                    Phase::init(Move::all(), CoordCube::is_domino, Vec::new()),
+                    Phase::init(up_down, CoordCube::is_solved, Vec::new()),
+                ],
+            ),
+            orientation: Orientation::default(),
+        });
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let _ = kociemba.solve_until(cube_with_moves("R2"), deadline);
+    }
 
-        #[test]
-        fn has_quickcheck_generated() {
-            let cube = Cube::solved().apply_all(Move::parse_sequence("R' F2 U'").unwrap());
-            assert!(CORNER_ORIENTATION.has(&cube));
-        }
+    #[test]
+    fn solved_is_optimal() {
+        let (moves, quality) = KOCIEMBA.solve_with_quality(Cube::solved());
+        assert_eq!(moves, vec![]);
+        assert_eq!(quality, SolutionQuality::Optimal);
+    }
 
-        #[test]
-        fn has_sune() {
-            let cube =
-                Cube::solved().apply_all(Move::parse_sequence("R U' R' U' R U2 R'").unwrap());
-            assert!(CORNER_ORIENTATION.has(&cube));
-        }
+    #[test]
+    fn long_solutions_are_best_found() {
+        let (_, quality) = KOCIEMBA.solve_with_quality(cube_with_moves("R2 U' L2 R2 L' B"));
+        assert_eq!(quality, SolutionQuality::BestFound);
+    }
 
-        #[quickcheck]
-        fn is_exhaustive(moves: Vec<Move>) -> bool {
-            let cube = Cube::solved().apply_all(moves);
-            CORNER_ORIENTATION.has(&cube)
-        }
+    #[test]
+    fn solve_all_matches_solve() {
+        let cubes = ["R2 U' L2 R2 L' B", "F2 B2 U", "R2 L2", "B'"]
+            .map(cube_with_moves)
+            .to_vec();
+
+        let batch = KOCIEMBA.solve_all(cubes.clone());
+
+        let individual = cubes
+            .into_iter()
+            .map(|c| KOCIEMBA.solve(c).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(batch, individual);
     }
 }
@@ -0,0 +1,294 @@
+use crate::prelude::*;
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A beginner's method, solving the cube a layer at a time as people learn to.
+///
+/// The cross goes on D, then its corners and the middle layer's edges are placed one by one, and
+/// the last layer is oriented and permuted in four steps. Solutions are long, but every stage
+/// after the cross is a chain of well known algorithms and turns of U.
+pub struct LayerByLayer<E: Evaluator> {
+    evaluator: PhantomData<E>,
+}
+
+/// The faces around D, each followed by the one to its right when looking at it.
+const SIDES: [Face; 4] = [Face::Front, Face::Right, Face::Back, Face::Left];
+
+/// The most algorithms a stage chains, enough for any solvable cube.
+const MAX_ALGORITHMS: usize = 7;
+
+impl<E: Evaluator> Solver<E> for LayerByLayer<E> {
+    fn init(_: Challenge<E>) -> Self {
+        LayerByLayer {
+            evaluator: PhantomData,
+        }
+    }
+
+    fn solve(self: &Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
+        Box::new(self.solve_blocking(cube).into_iter())
+    }
+
+    fn solve_blocking(self: &Arc<Self>, cube: Cube) -> Vec<Move> {
+        self.solve_stages(&cube)
+            .into_iter()
+            .flat_map(|(_, moves)| moves)
+            .collect()
+    }
+}
+
+impl<E: Evaluator> LayerByLayer<E> {
+    /// The solution split into its named stages, in order.
+    pub fn solve_stages(&self, cube: &Cube) -> Vec<(&'static str, Vec<Move>)> {
+        let mut cube = cube.clone();
+        let mut stages = Vec::new();
+
+        let cross = solve_cross(&cube, Face::Down);
+        cube = cube.apply_all(cross.iter().copied());
+        stages.push(("Cross", cross));
+
+        let mut stickers = SIDES
+            .iter()
+            .flat_map(|&side| stickers_of(&[Face::Down, side]))
+            .collect::<Vec<_>>();
+
+        let mut corners = Vec::new();
+        for front in SIDES {
+            let mut algorithms = u_turns();
+            for insert in [
+                "R U R'",
+                "R U' R'",
+                "F' U' F",
+                "F' U F",
+                "R U2 R' U' R U R'",
+            ] {
+                algorithms.push(relabel(insert, front));
+            }
+            algorithms.extend(other_sides(front).map(|side| relabel("R U R'", side)));
+
+            stickers.extend(stickers_of(&[Face::Down, front, right_of(front)]));
+            let moves = chain(&cube, &algorithms, |c| all_solved(c, &stickers));
+            cube = cube.apply_all(moves.iter().copied());
+            corners.extend(moves);
+        }
+        stages.push(("First layer corners", corners));
+
+        let mut edges = Vec::new();
+        for front in SIDES {
+            let right_insert = "U R U' R' U' F' U F";
+            let mut algorithms = u_turns();
+            algorithms.push(relabel(right_insert, front));
+            algorithms.push(relabel("U' F' U F U R U' R'", front));
+            algorithms.extend(other_sides(front).map(|side| relabel(right_insert, side)));
+
+            stickers.extend(stickers_of(&[front, right_of(front)]));
+            let moves = chain(&cube, &algorithms, |c| all_solved(c, &stickers));
+            cube = cube.apply_all(moves.iter().copied());
+            edges.extend(moves);
+        }
+        stages.push(("Second layer edges", edges));
+
+        let last_layer = [
+            (
+                "Last layer edge orientation",
+                "F R U R' U' F'",
+                edges_oriented as fn(&Cube) -> bool,
+            ),
+            (
+                "Last layer corner orientation",
+                "R U R' U R U2 R'",
+                |c: &Cube| c.last_layer_oriented(Face::Down),
+            ),
+            (
+                "Last layer corner permutation",
+                "R' F R' B2 R F' R' B2 R2",
+                corners_solved,
+            ),
+            (
+                "Last layer edge permutation",
+                "R U' R U R U R U' R' U' R2",
+                |c: &Cube| *c == Cube::solved(),
+            ),
+        ];
+        for (name, algorithm, goal) in last_layer {
+            let mut algorithms = u_turns();
+            algorithms.push(Move::parse_sequence(algorithm).unwrap());
+            let moves = chain(&cube, &algorithms, goal);
+            cube = cube.apply_all(moves.iter().copied());
+            stages.push((name, moves));
+        }
+
+        stages
+    }
+}
+
+fn u_turns() -> Vec<Vec<Move>> {
+    enum_iterator::all::<Direction>()
+        .map(|direction| {
+            vec![Move {
+                face: Face::Up,
+                direction,
+            }]
+        })
+        .collect()
+}
+
+fn right_of(front: Face) -> Face {
+    let index = SIDES.iter().position(|&s| s == front).unwrap();
+    SIDES[(index + 1) % 4]
+}
+
+fn other_sides(front: Face) -> impl Iterator<Item = Face> {
+    SIDES.into_iter().filter(move |&s| s != front)
+}
+
+/// `algorithm` as written for the F and R sides, played with `front` as F instead.
+fn relabel(algorithm: &str, front: Face) -> Vec<Move> {
+    let right = right_of(front);
+    Move::parse_sequence(algorithm)
+        .unwrap()
+        .into_iter()
+        .map(|m| Move {
+            face: match m.face {
+                Face::Front => front,
+                Face::Right => right,
+                Face::Back => front.opposite(),
+                Face::Left => right.opposite(),
+                face => face,
+            },
+            ..m
+        })
+        .collect()
+}
+
+/// The fewest `algorithms` taking `cube` to `goal`, played one after another.
+fn chain(cube: &Cube, algorithms: &[Vec<Move>], goal: impl Fn(&Cube) -> bool) -> Vec<Move> {
+    (0..=MAX_ALGORITHMS)
+        .find_map(|depth| chain_within(cube, algorithms, &goal, depth, false))
+        .expect("cube should be solvable")
+}
+
+fn chain_within(
+    cube: &Cube,
+    algorithms: &[Vec<Move>],
+    goal: &impl Fn(&Cube) -> bool,
+    depth: usize,
+    after_u_turn: bool,
+) -> Option<Vec<Move>> {
+    if goal(cube) {
+        return Some(Vec::new());
+    }
+    if depth == 0 {
+        return None;
+    }
+
+    algorithms.iter().find_map(|algorithm| {
+        let u_turn = algorithm.len() == 1;
+        if after_u_turn && u_turn {
+            return None;
+        }
+
+        let next = cube.clone().apply_all(algorithm.iter().copied());
+        let mut rest = chain_within(&next, algorithms, goal, depth - 1, u_turn)?;
+        rest.splice(0..0, algorithm.iter().copied());
+        Some(rest)
+    })
+}
+
+fn sorted(mut faces: Vec<Face>) -> Vec<Face> {
+    faces.sort();
+    faces
+}
+
+fn piece_of(location: Location) -> Vec<Face> {
+    match location {
+        Location::Center(f) => vec![f],
+        Location::Edge(a, b) => sorted(vec![a, b]),
+        Location::Corner(a, b, c) => sorted(vec![a, b, c]),
+    }
+}
+
+/// The stickers of the piece between `faces`.
+fn stickers_of(faces: &[Face]) -> Vec<Location> {
+    let piece = sorted(faces.to_vec());
+    Location::all().filter(|&l| piece_of(l) == piece).collect()
+}
+
+fn all_solved(cube: &Cube, stickers: &[Location]) -> bool {
+    stickers.iter().all(|&l| cube.get(l) == l.face())
+}
+
+fn edges_oriented(cube: &Cube) -> bool {
+    cube.f2l_solved(Face::Down)
+        && SIDES
+            .iter()
+            .all(|&side| cube.get(Location::Edge(Face::Up, side)) == Face::Up)
+}
+
+fn corners_solved(cube: &Cube) -> bool {
+    cube.f2l_solved(Face::Down)
+        && Location::all()
+            .filter(|l| matches!(l, Location::Corner(..)))
+            .all(|l| cube.get(l) == l.face())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+
+    fn solver() -> Arc<LayerByLayer<BlastMachineEvaluator>> {
+        Arc::new(LayerByLayer::init(Challenge::new(
+            BlastMachineEvaluator::default(),
+        )))
+    }
+
+    #[test]
+    fn relabels_for_each_side() {
+        assert_eq!(relabel("R U R'", Face::Front), moves![R, U, Rp]);
+        assert_eq!(relabel("R U R'", Face::Right), moves![B, U, Bp]);
+        assert_eq!(relabel("F' U' F", Face::Left), moves![Lp, Up, L]);
+    }
+
+    #[test]
+    fn solves_main_scramble() {
+        let cube = cube_with_moves("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2");
+        let stages = solver().solve_stages(&cube);
+
+        let names = stages.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Cross",
+                "First layer corners",
+                "Second layer edges",
+                "Last layer edge orientation",
+                "Last layer corner orientation",
+                "Last layer corner permutation",
+                "Last layer edge permutation",
+            ]
+        );
+
+        let solution = stages.into_iter().flat_map(|(_, moves)| moves);
+        assert!(cube.apply_all(solution) == Cube::solved());
+    }
+
+    #[test]
+    fn solve_blocking_solves() {
+        let cube = cube_with_moves("R U2 F' L D' B2 R' U L2 F");
+        let solution = solver().solve_blocking(cube.clone());
+        assert!(cube.apply_all(solution) == Cube::solved());
+    }
+
+    /// Every stage finishes within `MAX_ALGORITHMS`, or `chain` would panic.
+    #[quickcheck]
+    fn solves_any_scramble(scramble: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(scramble);
+        cube.clone().apply_all(solver().solve_blocking(cube)) == Cube::solved()
+    }
+
+    #[test]
+    fn solved_needs_nothing() {
+        assert_eq!(solver().solve_blocking(Cube::solved()), vec![]);
+    }
+}
@@ -1,25 +1,103 @@
 use crate::prelude::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Each cube a side of the search has reached, with every sequence of moves reaching it.
+type Paths = HashMap<Cube, Vec<Vec<Move>>>;
 
 pub struct Mitm<E: Evaluator> {
-    #[allow(unused)]
     challenge: Challenge<E>,
+    max_states: Option<usize>,
 }
 
-impl<E: Evaluator> super::Solver<E> for Mitm<E> {
-    fn init(challenge: Challenge<E>) -> Self {
-        Mitm { challenge }
+/// What a search cost, whether or not it found a solution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MitmStats {
+    /// The most cubes held at once, from both sides of the search.
+    pub peak_states: usize,
+    /// How many times the frontiers were expanded.
+    pub expansions: usize,
+}
+
+impl<E: Evaluator> Mitm<E> {
+    /// Give up once the search holds more than `states` cubes, instead of running out of memory.
+    pub fn max_states(mut self, states: usize) -> Self {
+        self.max_states = Some(states);
+        self
     }
 
-    fn solve(self: &std::sync::Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
+    /// The solution, or `None` if the search grew past the `max_states`, with what it cost.
+    pub fn try_solve(&self, cube: &Cube) -> (Option<Vec<Move>>, MitmStats) {
+        let max = self.max_states.unwrap_or(usize::MAX);
         let mut state = SolveState::default();
-        for depth in 0..11usize {
-            dbg!(depth);
-            if let Some(solution) = state.expand(&cube) {
-                dbg!(state.forward.len());
-                dbg!(state.reverse.len());
-                return Box::new(solution.into_iter());
+        let mut stats = MitmStats::default();
+        for _ in 0..11usize {
+            let solution = state.expand(cube, max);
+            stats.expansions += 1;
+            stats.peak_states = stats
+                .peak_states
+                .max(state.forward.len() + state.reverse.len());
+            match solution {
+                Ok(None) => {}
+                Ok(solution) => return (solution, stats),
+                Err(TooManyStates) => {
+                    log::log!(
+                        self.challenge.log_level(),
+                        "Gave up without a solution holding {} states, more than {}",
+                        stats.peak_states,
+                        max
+                    );
+                    return (None, stats);
+                }
+            }
+        }
+
+        unreachable!();
+    }
+
+    /// Every solution with the fewest moves, or none if the search grew past the `max_states`.
+    ///
+    /// Both sides keep every path to each cube, rather than the first, so all the ways the
+    /// frontiers meet at the shortest depth are found. That's many more paths than cubes, so
+    /// this only suits short solutions.
+    pub fn solve_all_optimal(&self, cube: &Cube) -> Vec<Vec<Move>> {
+        if *cube == Cube::solved() {
+            return vec![Vec::new()];
+        }
+
+        let max = self.max_states.unwrap_or(usize::MAX);
+        let mut forward = Paths::from([(cube.clone(), vec![Vec::new()])]);
+        let mut reverse = Paths::from([(Cube::solved(), vec![Vec::new()])]);
+        for depth in 0..22usize {
+            let expanded = if depth % 2 == 0 {
+                expand_all(&forward, max.saturating_sub(reverse.len())).map(|next| forward = next)
+            } else {
+                expand_all(&reverse, max.saturating_sub(forward.len())).map(|next| reverse = next)
+            };
+            if let Err(TooManyStates) = expanded {
+                log::log!(
+                    self.challenge.log_level(),
+                    "Gave up without a solution holding more than {} states",
+                    max
+                );
+                return Vec::new();
+            }
+
+            let mut solutions = Vec::new();
+            for (cube, forward_paths) in &forward {
+                let Some(reverse_paths) = reverse.get(cube) else {
+                    continue;
+                };
+                for f in forward_paths {
+                    for r in reverse_paths {
+                        solutions.push([&f[..], &Move::invert_sequence(r)].concat());
+                    }
+                }
+            }
+            if !solutions.is_empty() {
+                let mut seen = HashSet::new();
+                solutions.retain(|solution| seen.insert(solution.clone()));
+                return solutions;
             }
         }
 
@@ -27,6 +105,29 @@ impl<E: Evaluator> super::Solver<E> for Mitm<E> {
     }
 }
 
+impl<E: Evaluator> super::Solver<E> for Mitm<E> {
+    fn init(challenge: Challenge<E>) -> Self {
+        Mitm {
+            challenge,
+            max_states: None,
+        }
+    }
+
+    /// No moves at all when the search grows past the `max_states`.
+    fn solve(self: &std::sync::Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
+        let (solution, stats) = self.try_solve(&cube);
+        log::debug!("{:?}", stats);
+        Box::new(solution.unwrap_or_default().into_iter())
+    }
+}
+
+/// The moves from each side to where they met, `this` side's first.
+type Meeting = (Vec<Move>, Vec<Move>);
+
+/// A search stopped partway through an expansion, as soon as it held more than its
+/// `max_states`.
+struct TooManyStates;
+
 #[derive(Default)]
 struct SolveState {
     forward: HashMap<Cube, Vec<Move>>,
@@ -34,62 +135,165 @@ struct SolveState {
 }
 
 impl SolveState {
-    fn expand(&mut self, initial: &Cube) -> Option<Vec<Move>> {
-        if self.forward.len() == 0 {
+    fn expand(&mut self, initial: &Cube, max: usize) -> Result<Option<Vec<Move>>, TooManyStates> {
+        if self.forward.is_empty() {
             assert_eq!(self.reverse.len(), 0);
             if *initial == Cube::solved() {
-                return Some(Vec::new());
+                return Ok(Some(Vec::new()));
             }
 
             self.forward.insert(initial.clone(), Vec::new());
             self.reverse.insert(Cube::solved(), Vec::new());
-            return None;
+            return Ok(None);
         }
 
-        if let Some((forward, rev)) = Self::expand_mut(&mut self.forward, &mut self.reverse) {
-            return Some(forward.into_iter().chain(reverse(rev)).collect());
+        if let Some((forward, rev)) = Self::expand_mut(&mut self.forward, &mut self.reverse, max)? {
+            return Ok(Some(forward.into_iter().chain(reverse(rev)).collect()));
         }
 
-        if let Some((rev, forward)) = Self::expand_mut(&mut self.reverse, &mut self.forward) {
-            return Some(forward.into_iter().chain(reverse(rev)).collect());
+        if let Some((rev, forward)) = Self::expand_mut(&mut self.reverse, &mut self.forward, max)? {
+            return Ok(Some(forward.into_iter().chain(reverse(rev)).collect()));
         }
 
-        return None;
+        Ok(None)
     }
 
+    /// Replace `this` side's cubes with those one move further, stopping once it meets `other`
+    /// or once both sides hold more than `max` cubes between them.
     fn expand_mut(
         this: &mut HashMap<Cube, Vec<Move>>,
         other: &mut HashMap<Cube, Vec<Move>>,
-    ) -> Option<(Vec<Move>, Vec<Move>)> {
-        let expand = this
-            .drain()
-            .flat_map(|(cube, moves)| {
-                Move::all().map(move |move_| {
-                    (cube.clone().apply(move_), {
-                        let mut m = moves.clone();
-                        m.push(move_);
-                        m
-                    })
-                })
-            })
-            .collect::<Vec<_>>();
-
-        for (cube, moves) in expand {
-            if let Some(other) = other.remove(&cube) {
-                return Some((moves, other));
-            }
+        max: usize,
+    ) -> Result<Option<Meeting>, TooManyStates> {
+        for (cube, moves) in std::mem::take(this) {
+            for move_ in Move::all() {
+                let cube = cube.clone().apply(move_);
+                let mut moves = moves.clone();
+                moves.push(move_);
+
+                if let Some(other) = other.remove(&cube) {
+                    return Ok(Some((moves, other)));
+                }
 
-            this.entry(cube).or_insert(moves);
+                this.entry(cube).or_insert(moves);
+                if this.len() + other.len() > max {
+                    return Err(TooManyStates);
+                }
+            }
         }
 
-        return None;
+        Ok(None)
+    }
+}
+
+/// The cubes one move past `frontier`, never turning the same face twice in a row, or
+/// `TooManyStates` as soon as there are more than `max`.
+fn expand_all(frontier: &Paths, max: usize) -> Result<Paths, TooManyStates> {
+    let mut next = Paths::new();
+    for (cube, paths) in frontier {
+        for move_ in Move::all() {
+            let extended = paths
+                .iter()
+                .filter(|path| path.last().is_none_or(|last| last.face != move_.face))
+                .map(|path| [&path[..], &[move_]].concat())
+                .collect::<Vec<_>>();
+            if !extended.is_empty() {
+                next.entry(cube.clone().apply(move_))
+                    .or_default()
+                    .extend(extended);
+                if next.len() > max {
+                    return Err(TooManyStates);
+                }
+            }
+        }
     }
+    Ok(next)
 }
 
 fn reverse(moves: Vec<Move>) -> Vec<Move> {
-    if moves.len() == 0 {
+    if moves.is_empty() {
         return moves;
     }
 
     moves.into_iter().rev().map(|m| m.reverse()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    fn per_move(seq: &[Move]) -> Duration {
+        Duration::from_millis(10) * seq.len() as u32
+    }
+
+    fn solver() -> Mitm<fn(&[Move]) -> Duration> {
+        Mitm::init(Challenge::new(per_move))
+    }
+
+    #[test]
+    fn solves_short_scramble() {
+        let cube = cube_with_moves("R U F'");
+        let (solution, stats) = solver().try_solve(&cube);
+        let solution = solution.unwrap();
+
+        assert_eq!(solution.len(), 3);
+        assert_eq!(cube.apply_all(solution), Cube::solved());
+        assert!(stats.peak_states > 0);
+    }
+
+    #[test]
+    fn solved_holds_nothing() {
+        let (solution, stats) = solver().try_solve(&Cube::solved());
+        assert_eq!(solution, Some(vec![]));
+        assert_eq!(stats.peak_states, 0);
+    }
+
+    #[test]
+    fn all_optimal_includes_every_order() {
+        let solutions = solver().solve_all_optimal(&cube_with_moves("R L"));
+        assert_eq!(
+            solutions.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([moves![Rp, Lp], moves![Lp, Rp]])
+        );
+    }
+
+    #[test]
+    fn all_optimal_are_shortest_solutions() {
+        let cube = cube_with_moves("R U F' D2");
+        let (shortest, _) = solver().try_solve(&cube);
+        let solutions = solver().solve_all_optimal(&cube);
+
+        assert!(solutions.contains(&shortest.unwrap()));
+        for solution in &solutions {
+            assert_eq!(solution.len(), 4);
+            assert_eq!(cube.clone().apply_all(solution.clone()), Cube::solved());
+        }
+        let unique = solutions.iter().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), solutions.len());
+    }
+
+    #[test]
+    fn all_optimal_of_solved_is_nothing() {
+        assert_eq!(solver().solve_all_optimal(&Cube::solved()), vec![vec![]]);
+    }
+
+    #[test]
+    fn gives_up_past_max_states() {
+        let no_states = Arc::new(solver().max_states(0));
+        let solver = Arc::new(solver().max_states(10_000));
+        let cube = cube_with_moves("R2 U' L2 R2 B2 F2 L2 U' L' B D F R2 L2");
+
+        let (solution, stats) = solver.try_solve(&cube);
+        assert_eq!(solution, None);
+        assert_eq!(stats.peak_states, 10_001);
+        assert!(stats.expansions < 11);
+        assert_eq!(solver.solve_blocking(cube.clone()), vec![]);
+        assert_eq!(solver.solve_all_optimal(&cube), Vec::<Vec<Move>>::new());
+
+        assert_eq!(no_states.try_solve(&cube).0, None);
+        assert_eq!(no_states.solve_blocking(cube.clone()), vec![]);
+        assert_eq!(no_states.solve_all_optimal(&cube), Vec::<Vec<Move>>::new());
+    }
+}
@@ -1,17 +1,125 @@
 use crate::prelude::*;
 use std::sync::Arc;
 
+mod cross;
+pub use cross::*;
+
 mod kociemba;
 pub use kociemba::*;
 
+mod layer_by_layer;
+pub use layer_by_layer::*;
+
 mod naive_iddfs;
 pub use naive_iddfs::*;
 
 mod mitm;
 pub use mitm::*;
 
+mod pattern_database;
+pub use pattern_database::*;
+
+mod phase;
+pub use phase::*;
+
+mod phased;
+pub use phased::*;
+
+mod pocket;
+pub use pocket::*;
+
 pub trait Solver<E: Evaluator>: Sized {
     fn init(challenge: Challenge<E>) -> Self;
 
     fn solve(self: &Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>>;
+
+    /// Solve on the calling thread, for targets without threads like `wasm32-unknown-unknown`.
+    fn solve_blocking(self: &Arc<Self>, cube: Cube) -> Vec<Move> {
+        self.solve(cube).collect()
+    }
+}
+
+/// A `Solver` that can be chosen at runtime and kept as a `Box<dyn DynSolver<E>>`, see `boxed`.
+pub trait DynSolver<E: Evaluator> {
+    fn solve(&self, cube: Cube) -> Box<dyn Iterator<Item = Move>>;
+}
+
+/// Wraps the `Arc` rather than implementing `DynSolver` on it, where its `solve` would be
+/// ambiguous with `Solver::solve`.
+struct Shared<S>(Arc<S>);
+
+impl<E: Evaluator, S: Solver<E>> DynSolver<E> for Shared<S> {
+    fn solve(&self, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
+        self.0.solve(cube)
+    }
+}
+
+/// Initialize `S` behind the object safe `DynSolver`.
+pub fn boxed<E: Evaluator, S: Solver<E> + 'static>(
+    challenge: Challenge<E>,
+) -> Box<dyn DynSolver<E>> {
+    Box::new(Shared(Arc::new(S::init(challenge))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+
+    fn challenge() -> Challenge<BlastMachineEvaluator> {
+        Challenge::new(BlastMachineEvaluator::default())
+    }
+
+    #[test]
+    fn solves_through_dyn_solver() {
+        let solvers: Vec<Box<dyn DynSolver<BlastMachineEvaluator>>> = vec![
+            Box::new(Shared(Arc::clone(&KOCIEMBA))),
+            boxed::<_, Mitm<_>>(challenge()),
+            boxed::<_, NaiveIddfs<_>>(challenge()),
+            boxed::<_, LayerByLayer<_>>(challenge()),
+        ];
+
+        let cube = cube_with_moves("R U F'");
+        for solver in solvers {
+            let solution = solver.solve(cube.clone()).collect::<Vec<_>>();
+            assert_eq!(cube.clone().apply_all(solution), Cube::solved());
+        }
+    }
+
+    /// Solves with each solver that runs on the calling thread, giving up on some.
+    fn solve_logging(challenge: impl Fn() -> Challenge<BlastMachineEvaluator>) {
+        let cube = cube_with_moves("R U F'");
+        Arc::new(PocketSolver::init(challenge())).solve_blocking(cube.clone());
+        Arc::new(NaiveIddfs::init(challenge()).max_depth(1)).solve_blocking(cube.clone());
+        Arc::new(Mitm::init(challenge()).max_states(10)).solve_blocking(cube.clone());
+    }
+
+    #[test]
+    fn quiet_logs_nothing_above_debug() {
+        let quiet = || Challenge {
+            quiet: true,
+            ..challenge()
+        };
+        let logs = captured_logs(|| solve_logging(quiet));
+        assert!(!logs.is_empty());
+        assert!(
+            logs.iter().all(|&(level, _)| level >= log::Level::Debug),
+            "{:?}",
+            logs
+        );
+    }
+
+    #[test]
+    fn loud_logs_at_info() {
+        let logs = captured_logs(|| solve_logging(challenge));
+        let info = logs
+            .iter()
+            .filter(|&&(level, _)| level == log::Level::Info)
+            .map(|(_, message)| message.as_str())
+            .collect::<Vec<_>>();
+        assert!(info
+            .iter()
+            .any(|m| m.starts_with("pocket_corner_position: Finished")));
+        assert!(info.iter().any(|m| m.starts_with("Gave up")));
+    }
 }
@@ -1,12 +1,53 @@
+use crate::cube::coord::CoordCube;
 use crate::prelude::*;
 
 use std::collections::VecDeque;
 
 pub struct NaiveIddfs<E: Evaluator> {
     challenge: Challenge<E>,
+    /// A lower bound on the moves left, as the time they take at a fixed time per move.
+    heuristic: Option<(Box<dyn Heuristic>, Duration)>,
+    max_depth: Option<u8>,
 }
 
 impl<E: Evaluator> NaiveIddfs<E> {
+    /// Skip sequences that `heuristic` shows can't finish in the moves left, making the search
+    /// IDA*. Its bound is read as a number of moves taking `per_move` each, as from a
+    /// `PatternDatabase` built with an evaluator charging `per_move` for every move.
+    ///
+    /// Solutions keep the fewest moves as long as the bound never overestimates.
+    pub fn with_heuristic(
+        mut self,
+        heuristic: impl Heuristic + 'static,
+        per_move: Duration,
+    ) -> Self {
+        self.heuristic = Some((Box::new(heuristic), per_move));
+        self
+    }
+
+    /// Give up on cubes that need more than `depth` moves, instead of searching forever.
+    pub fn max_depth(mut self, depth: u8) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// The solution, or `None` if it would take more than the `max_depth`.
+    pub fn try_solve(&self, cube: &Cube) -> Option<Vec<Move>> {
+        let max_depth = self.max_depth.unwrap_or(u8::MAX);
+        let solution = (0..=max_depth).find_map(|depth| self.find_solution(depth, cube, None));
+        if solution.is_none() {
+            log::log!(
+                self.challenge.log_level(),
+                "Gave up without a solution in {} moves",
+                max_depth
+            );
+        }
+        solution.map(Vec::from)
+    }
+
+    /// The fastest solution in exactly `remaining_moves`. Equally fast ones are told apart by
+    /// comparing their moves' `Move::index`es in order, keeping the smallest, so the choice
+    /// doesn't depend on the order moves are tried in.
     fn find_solution(
         &self,
         remaining_moves: u8,
@@ -21,6 +62,13 @@ impl<E: Evaluator> NaiveIddfs<E> {
             }
         }
 
+        if let Some((heuristic, per_move)) = &self.heuristic {
+            let min_time = heuristic.min_time(&CoordCube::from(cube.clone()));
+            if min_time > *per_move * remaining_moves as u32 {
+                return None;
+            }
+        }
+
         Move::all()
             .filter(|move_| match last_move {
                 None => true,
@@ -32,24 +80,154 @@ impl<E: Evaluator> NaiveIddfs<E> {
                 solution.push_front(move_);
                 Some(solution)
             })
-            .min_by_key(|seq| {
-                self.challenge
+            .fold(None, |best: Option<(Duration, VecDeque<Move>)>, mut seq| {
+                let bound = best.as_ref().map_or(Duration::MAX, |(time, _)| *time);
+                let earlier = best.as_ref().is_some_and(|(_, best)| {
+                    seq.iter().map(Move::index).lt(best.iter().map(Move::index))
+                });
+                match self
+                    .challenge
                     .evaluator
-                    .eval(seq.iter().cloned().collect::<Vec<_>>().as_slice())
+                    .eval_bounded(seq.make_contiguous(), bound)
+                {
+                    Some(time) if time < bound => Some((time, seq)),
+                    Some(_) if earlier => Some((bound, seq)),
+                    _ => best,
+                }
             })
+            .map(|(_, seq)| seq)
     }
 }
 
 impl<E: Evaluator> super::Solver<E> for NaiveIddfs<E> {
     fn init(challenge: Challenge<E>) -> NaiveIddfs<E> {
-        NaiveIddfs { challenge }
+        NaiveIddfs {
+            challenge,
+            heuristic: None,
+            max_depth: None,
+        }
     }
 
+    /// No moves at all when the cube needs more than the `max_depth`.
     fn solve(self: &std::sync::Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
-        (0..)
-            .filter_map(|move_depth| self.find_solution(move_depth, &cube, None))
-            .map(|seq| Box::new(seq.into_iter()))
-            .next()
-            .expect("every cube is eventually solvable")
+        Box::new(self.try_solve(&cube).unwrap_or_default().into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn per_move(seq: &[Move]) -> Duration {
+        Duration::from_millis(10) * seq.len() as u32
+    }
+
+    fn challenge() -> Challenge<fn(&[Move]) -> Duration> {
+        Challenge::new(per_move)
+    }
+
+    /// The fewest moves to each value of `coordinate`, breadth first from solved.
+    fn distances(coordinate: fn(&CoordCube) -> u16) -> HashMap<u16, u32> {
+        let mut distances = HashMap::from([(0, 0)]);
+        let mut frontier = vec![Cube::solved()];
+        for depth in 1.. {
+            let mut next = Vec::new();
+            for cube in frontier {
+                for m in Move::all() {
+                    let moved = cube.clone().apply(m);
+                    let value = coordinate(&CoordCube::from(moved.clone()));
+                    distances.entry(value).or_insert_with(|| {
+                        next.push(moved);
+                        depth
+                    });
+                }
+            }
+            if next.is_empty() {
+                return distances;
+            }
+            frontier = next;
+        }
+        unreachable!()
+    }
+
+    fn orientations() -> impl Heuristic {
+        let corners = distances(CoordCube::corner_orientation);
+        let edges = distances(CoordCube::edge_orientation);
+        move |c: &CoordCube| {
+            let moves = corners[&c.corner_orientation()].max(edges[&c.edge_orientation()]);
+            Duration::from_millis(10) * moves
+        }
+    }
+
+    #[test]
+    fn solves_short_scramble() {
+        let solver = Arc::new(NaiveIddfs::init(challenge()));
+        let cube = cube_with_moves("R U'");
+        assert_eq!(solver.solve_blocking(cube), moves![U, Rp]);
+    }
+
+    #[test]
+    fn heuristic_solves_at_depth_six() {
+        let solver = Arc::new(
+            NaiveIddfs::init(challenge()).with_heuristic(orientations(), Duration::from_millis(10)),
+        );
+        let cube = cube_with_moves("R U F' L2 D B");
+        let solution = solver.solve_blocking(cube.clone());
+
+        assert_eq!(solution.len(), 6);
+        assert_eq!(cube.apply_all(solution), Cube::solved());
+    }
+
+    #[test]
+    fn heuristic_keeps_fewest_moves() {
+        let plain = Arc::new(NaiveIddfs::init(challenge()));
+        let pruned = Arc::new(
+            NaiveIddfs::init(challenge()).with_heuristic(orientations(), Duration::from_millis(10)),
+        );
+        for scramble in ["F R", "F B' U2", "R U R' U'"] {
+            let cube = cube_with_moves(scramble);
+            assert_eq!(
+                pruned.solve_blocking(cube.clone()).len(),
+                plain.solve_blocking(cube).len()
+            );
+        }
+    }
+
+    #[test]
+    fn ties_go_to_the_smallest_indexes() {
+        let solver = Arc::new(NaiveIddfs::init(challenge()));
+        assert_eq!(
+            solver.solve_blocking(cube_with_moves("U D")),
+            moves![Up, Dp]
+        );
+        assert_eq!(
+            solver.solve_blocking(cube_with_moves("D U")),
+            moves![Up, Dp]
+        );
+        assert_eq!(
+            solver.solve_blocking(cube_with_moves("R L2")),
+            moves![L2, Rp]
+        );
+    }
+
+    #[test]
+    fn gives_up_past_max_depth() {
+        let solver = Arc::new(NaiveIddfs::init(challenge()).max_depth(2));
+        let cube = cube_with_moves("R U F");
+
+        assert_eq!(solver.try_solve(&cube), None);
+        assert_eq!(solver.solve_blocking(cube), vec![]);
+    }
+
+    #[test]
+    fn solves_at_max_depth() {
+        let solver = NaiveIddfs::init(challenge()).max_depth(3);
+        assert_eq!(
+            solver.try_solve(&cube_with_moves("R U F")),
+            Some(moves![Fp, Up, Rp])
+        );
     }
 }
@@ -0,0 +1,837 @@
+use crate::cube::coord::{CoordCube, TableSize};
+use crate::prelude::*;
+
+use core::any::Any;
+use core::hash::Hash;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+};
+
+const MAGIC: &[u8; 4] = b"BCPD";
+
+/// Bump when a table's meaning changes in a way its cache key can't see, so stale cache files
+/// are rebuilt rather than loaded.
+const FORMAT_VERSION: u32 = 1;
+
+/// What `PatternDatabase::min_time` does when an exhaustive table has no entry for a cube. That
+/// can only happen if the simplifier reaches values the table's moves never did, which is a bug.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnMissing {
+    Panic,
+    /// Log a warning and fall back to a lower bound of zero, which is always admissible.
+    Warn,
+}
+
+impl Default for OnMissing {
+    /// Panic in debug builds, warn in release builds.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            OnMissing::Panic
+        } else {
+            OnMissing::Warn
+        }
+    }
+}
+
+/// Where to stop building a `PatternDatabase` that would otherwise take too long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableLimit {
+    /// Stop before the first depth that starts after this long. How far that gets depends on the
+    /// machine.
+    Time(Duration),
+    /// Expand sequences of up to this many moves, giving the same table on any machine.
+    Depth(usize),
+}
+
+/// A table of lower bounds on the time to solve any cube sharing a coordinate value.
+///
+/// The table is built breadth-first from the solved cube using only `allowed_moves`. Each
+/// coordinate value records the smallest `Evaluator::min_time` of the sequences that reach it,
+/// so lookups are admissible for any search restricted to the same moves and evaluator.
+///
+/// ```no_run
+/// use blastcube::cube::coord::CoordCube;
+/// use blastcube::prelude::*;
+///
+/// let moves = Move::all().collect::<Vec<_>>();
+/// let evaluator = |seq: &[Move]| Duration::from_millis(10) * seq.len() as u32;
+/// let corners = PatternDatabase::init(
+///     "corner_orientation",
+///     |c: &CoordCube| c.corner_orientation(),
+///     &moves,
+///     &evaluator,
+///     None,
+/// );
+///
+/// let cube = CoordCube::from(Cube::solved().apply("R".parse().unwrap()));
+/// assert!(corners.lookup(&cube).is_some());
+/// ```
+pub struct PatternDatabase<T: Eq + Hash, F> {
+    name: String,
+    exhaustive: bool,
+    on_missing: OnMissing,
+
+    /// Shared with other tables built the same way, see `shared_pattern_database`.
+    map: Arc<HashMap<T, Duration>>,
+    simplifier: F,
+}
+
+impl<T: Eq + Hash + core::fmt::Debug, F> PatternDatabase<T, F>
+where
+    F: Fn(&CoordCube) -> T,
+{
+    /// Build the table, stopping early at `limit`. A table cut short this way is not exhaustive,
+    /// and `lookup` may miss for reachable cubes.
+    pub fn init(
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        evaluator: &impl Evaluator,
+        limit: Option<TableLimit>,
+    ) -> Self {
+        Self::init_logged(
+            name,
+            simplifier,
+            allowed_moves,
+            evaluator,
+            limit,
+            log::Level::Info,
+        )
+    }
+
+    /// Like `init`, logging when the table is finished at `level` rather than `info`.
+    fn init_logged(
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        evaluator: &impl Evaluator,
+        limit: Option<TableLimit>,
+        level: log::Level,
+    ) -> Self {
+        let mut result = Self {
+            name: name.to_string(),
+            exhaustive: true,
+            on_missing: OnMissing::default(),
+
+            simplifier,
+            map: Arc::default(),
+        };
+
+        #[cfg(test)]
+        BUILDS.with(|b| b.set(b.get() + 1));
+
+        let start = std::time::Instant::now();
+        for depth in 0..21 {
+            log::debug!(
+                "{}: Expanding to depth: {}, {} items",
+                result.name,
+                depth,
+                result.map.len()
+            );
+            let should_break = match limit {
+                Some(TableLimit::Time(max)) if start.elapsed() >= max => {
+                    result.exhaustive = false;
+                    true
+                }
+                Some(TableLimit::Depth(max)) if depth > max => {
+                    result.exhaustive = false;
+                    true
+                }
+                _ => !result.expand_to_depth(depth, &mut Vec::new(), evaluator, allowed_moves),
+            };
+            if should_break {
+                log::log!(
+                    level,
+                    "{}: Finished expanding at depth {}, {} items, took {:?}",
+                    result.name,
+                    depth,
+                    result.map.len(),
+                    start.elapsed(),
+                );
+                break;
+            }
+        }
+
+        result
+    }
+
+    fn from_map(
+        name: &str,
+        simplifier: F,
+        exhaustive: bool,
+        map: impl Into<Arc<HashMap<T, Duration>>>,
+    ) -> Self {
+        PatternDatabase {
+            name: name.to_string(),
+            exhaustive,
+            on_missing: OnMissing::default(),
+            map: map.into(),
+            simplifier,
+        }
+    }
+
+    fn map_mut(&mut self) -> &mut HashMap<T, Duration> {
+        Arc::get_mut(&mut self.map).expect("Only tables being built are changed")
+    }
+
+    fn expand_to_depth(
+        &mut self,
+        depth: usize,
+        move_stack: &mut Vec<Move>,
+        evaluator: &impl Evaluator,
+        allowed_moves: &[Move],
+    ) -> bool {
+        let inv = Move::invert_sequence(move_stack);
+        if !Move::should_consider(&inv) {
+            return false;
+        }
+
+        let value = self.simplify_depr(&Cube::solved().apply_all(move_stack.clone()));
+        let time = evaluator.min_time(&inv);
+
+        let already = self.map.get(&value);
+        match (depth, already) {
+            (0, None) => {
+                self.map_mut().insert(value, time);
+                true
+            }
+            (0, Some(t)) if time < *t => {
+                self.map_mut().insert(value, time);
+                true
+            }
+            (0, Some(_)) => false,
+
+            (_, Some(t)) if *t < time => false,
+            (_, Some(_)) => allowed_moves.iter().fold(false, |any, move_| {
+                move_stack.push(*move_);
+                let result = self.expand_to_depth(depth - 1, move_stack, evaluator, allowed_moves);
+                move_stack.pop();
+                any || result
+            }),
+            (_, None) => unreachable!(),
+        }
+    }
+
+    /// The minimum time recorded for the cube's coordinate, if the table reached it.
+    pub fn lookup(&self, cube: &CoordCube) -> Option<Duration> {
+        self.map.get(&self.simplify(cube)).copied()
+    }
+
+    /// Lower bound on the time to solve the cube, zero if the table doesn't have its value.
+    ///
+    /// Misses are expected in tables that aren't exhaustive. In exhaustive ones they are handled
+    /// according to `on_missing`.
+    pub fn min_time(&self, cube: &CoordCube) -> Duration {
+        if let Some(d) = self.lookup(cube) {
+            return d;
+        }
+
+        if self.exhaustive {
+            match self.on_missing {
+                OnMissing::Panic => panic!(
+                    "{}: missing value ({:?}) for cube\n{:?}",
+                    self.name,
+                    self.simplify(cube),
+                    cube
+                ),
+                OnMissing::Warn => log::warn!(
+                    "{}: missing value ({:?}), using zero",
+                    self.name,
+                    self.simplify(cube),
+                ),
+            }
+        }
+        Duration::default()
+    }
+
+    pub fn on_missing(mut self, policy: OnMissing) -> Self {
+        self.on_missing = policy;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_exhaustive(&self) -> bool {
+        self.exhaustive
+    }
+
+    pub fn table_size(&self) -> TableSize {
+        TableSize {
+            name: self.name.clone(),
+            states: self.map.len(),
+        }
+    }
+
+    #[cfg(test)]
+    fn has(&self, cube: &Cube) -> bool {
+        let simplified = self.simplify_depr(cube);
+        self.map.contains_key(&simplified)
+    }
+
+    pub fn simplify(&self, cube: &CoordCube) -> T {
+        (self.simplifier)(cube)
+    }
+
+    fn simplify_depr(&self, cube: &Cube) -> T {
+        (self.simplifier)(&CoordCube::from(cube.clone()))
+    }
+}
+
+impl<T, F> PatternDatabase<T, F>
+where
+    T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64>,
+    F: Fn(&CoordCube) -> T,
+{
+    /// Like `init`, but first tries to load the table from `cache_dir`. The cache file is keyed
+    /// by the simplifier's values, the allowed moves, the evaluator's timings and `limit`, so
+    /// changing any of them builds (and saves) a fresh table.
+    pub fn init_cached(
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        evaluator: &impl Evaluator,
+        limit: Option<TableLimit>,
+        cache_dir: &Path,
+    ) -> Self {
+        Self::init_cached_logged(
+            name,
+            simplifier,
+            allowed_moves,
+            evaluator,
+            limit,
+            cache_dir,
+            log::Level::Info,
+        )
+    }
+
+    /// Like `init_cached`, logging when the table is loaded or built at `level` rather than
+    /// `info`.
+    fn init_cached_logged(
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        evaluator: &impl Evaluator,
+        limit: Option<TableLimit>,
+        cache_dir: &Path,
+        level: log::Level,
+    ) -> Self {
+        let key = cache_key(&simplifier, allowed_moves, evaluator, limit);
+        let path = cache_dir.join(format!("{}-{:016x}.bin", name, key));
+
+        match File::open(&path).and_then(|f| read_map(BufReader::new(f))) {
+            Ok((exhaustive, map)) => {
+                log::log!(
+                    level,
+                    "{}: Loaded {} items from {:?}",
+                    name,
+                    map.len(),
+                    path
+                );
+                return Self::from_map(name, simplifier, exhaustive, map);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("{}: Ignoring unreadable cache {:?}: {}", name, path, e),
+        }
+
+        let result = Self::init_logged(name, simplifier, allowed_moves, evaluator, limit, level);
+        let saved = std::fs::create_dir_all(cache_dir)
+            .and_then(|_| File::create(&path))
+            .and_then(|f| result.save(BufWriter::new(f)));
+        if let Err(e) = saved {
+            log::warn!("{}: Failed to save cache {:?}: {}", name, path, e);
+        }
+        result
+    }
+
+    pub fn save(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut entries = self
+            .map
+            .iter()
+            .map(|(&value, time)| (value.into(), time.as_nanos() as u64))
+            .collect::<Vec<(u64, u64)>>();
+        entries.sort_unstable();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[self.exhaustive as u8])?;
+        writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+        for (value, nanos) in entries {
+            writer.write_all(&value.to_le_bytes())?;
+            writer.write_all(&nanos.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    pub fn load(name: &str, simplifier: F, reader: impl Read) -> io::Result<Self> {
+        let (exhaustive, map) = read_map(reader)?;
+        Ok(Self::from_map(name, simplifier, exhaustive, map))
+    }
+}
+
+fn read_map<T>(mut reader: impl Read) -> io::Result<(bool, HashMap<T, Duration>)>
+where
+    T: Eq + Hash + TryFrom<u64>,
+{
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a pattern database"));
+    }
+
+    let mut exhaustive = [0];
+    reader.read_exact(&mut exhaustive)?;
+
+    let len = read_u64(&mut reader)?;
+    // The length isn't trusted to size the map, a corrupt one would fail to allocate.
+    let mut map = HashMap::new();
+    for _ in 0..len {
+        let value =
+            T::try_from(read_u64(&mut reader)?).map_err(|_| invalid("value out of range"))?;
+        let time = Duration::from_nanos(read_u64(&mut reader)?);
+        map.insert(value, time);
+    }
+
+    Ok((exhaustive[0] != 0, map))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+// FNV-1a, which unlike `DefaultHasher` is stable across Rust versions.
+fn cache_key<T: Into<u64>>(
+    simplifier: &impl Fn(&CoordCube) -> T,
+    allowed_moves: &[Move],
+    evaluator: &impl Evaluator,
+    limit: Option<TableLimit>,
+) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut write = |bytes: &[u8]| {
+        for b in bytes {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+
+    write(&FORMAT_VERSION.to_le_bytes());
+    for m in allowed_moves {
+        write(m.to_string().as_bytes());
+    }
+    let (kind, amount) = match limit {
+        None => (0, 0),
+        Some(TableLimit::Time(d)) => (1, d.as_nanos() as u64),
+        Some(TableLimit::Depth(depth)) => (2, depth as u64),
+    };
+    write(&[kind]);
+    write(&amount.to_le_bytes());
+
+    // Evaluators and coordinates have no identity, so fingerprint them by their timings and
+    // values on short sequences.
+    let probes = core::iter::once(Vec::new())
+        .chain(Move::all().map(|m| vec![m]))
+        .chain(Move::all().flat_map(|a| Move::all().map(move |b| vec![a, b])));
+    for seq in probes {
+        write(&(evaluator.eval(&seq).as_nanos() as u64).to_le_bytes());
+        write(&(evaluator.min_time(&seq).as_nanos() as u64).to_le_bytes());
+        let cube = CoordCube::from(Cube::solved().apply_all(seq));
+        write(&simplifier(&cube).into().to_le_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Tables built by `PatternDatabase::init` on this thread, rather than loaded.
+    pub(super) static BUILDS: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+}
+
+/// Build a table, or load it from the challenge's `table_cache` when it has one, logging at the
+/// challenge's `log_level`.
+pub(super) fn pattern_database<T, F>(
+    name: &str,
+    simplifier: F,
+    allowed_moves: &[Move],
+    challenge: &Challenge<impl Evaluator>,
+    limit: Option<TableLimit>,
+) -> PatternDatabase<T, F>
+where
+    T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64>,
+    F: Fn(&CoordCube) -> T,
+{
+    let evaluator = &challenge.evaluator;
+    let level = challenge.log_level();
+    match &challenge.table_cache {
+        Some(dir) => PatternDatabase::init_cached_logged(
+            name,
+            simplifier,
+            allowed_moves,
+            evaluator,
+            limit,
+            dir,
+            level,
+        ),
+        None => {
+            PatternDatabase::init_logged(name, simplifier, allowed_moves, evaluator, limit, level)
+        }
+    }
+}
+
+/// What a shared table was built from. The evaluator is compared itself, rather than by its
+/// timings in `cache_key`, since evaluators that only differ on longer sequences need tables of
+/// their own.
+#[derive(PartialEq)]
+struct SharedKey<E> {
+    name: String,
+    cache_key: u64,
+    evaluator: E,
+}
+
+type SharedEntries = Vec<(Box<dyn Any + Send>, Weak<dyn Any + Send + Sync>)>;
+
+/// The entries of tables returned by `shared_pattern_database`, by their `SharedKey` and whether
+/// they are exhaustive. Only held weakly, so they are freed with the last table using them.
+static SHARED: Mutex<SharedEntries> = Mutex::new(Vec::new());
+
+/// Like `pattern_database`, but shares the entries with any table still alive that was built the
+/// same way, rather than building another.
+pub(super) fn shared_pattern_database<T, F, E>(
+    name: &str,
+    simplifier: F,
+    allowed_moves: &[Move],
+    challenge: &Challenge<E>,
+    limit: Option<TableLimit>,
+) -> PatternDatabase<T, F>
+where
+    T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64> + Send + Sync + 'static,
+    F: Fn(&CoordCube) -> T,
+    E: Evaluator + PartialEq + Clone,
+{
+    let key = SharedKey {
+        name: name.to_string(),
+        cache_key: cache_key(&simplifier, allowed_moves, &challenge.evaluator, limit),
+        evaluator: challenge.evaluator.clone(),
+    };
+
+    let found = {
+        let mut shared = SHARED.lock().unwrap();
+        shared.retain(|(_, map)| map.strong_count() > 0);
+        shared.iter().find_map(|(built, map)| {
+            let (built, exhaustive) = built.downcast_ref::<(SharedKey<E>, bool)>()?;
+            if *built != key {
+                return None;
+            }
+            let map = map.upgrade()?.downcast::<HashMap<T, Duration>>().ok()?;
+            Some((*exhaustive, map))
+        })
+    };
+    if let Some((exhaustive, map)) = found {
+        log::debug!("{}: Sharing {} items built earlier", name, map.len());
+        return PatternDatabase::from_map(name, simplifier, exhaustive, map);
+    }
+
+    let table = pattern_database(name, simplifier, allowed_moves, challenge, limit);
+    let map = Arc::downgrade(&table.map);
+    SHARED
+        .lock()
+        .unwrap()
+        .push((Box::new((key, table.exhaustive)), map));
+    table
+}
+
+/// Where a solver gets its heuristic tables from.
+pub(super) trait TableSource<E: Evaluator> {
+    fn table<T, F>(
+        &self,
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        challenge: &Challenge<E>,
+        limit: Option<TableLimit>,
+    ) -> PatternDatabase<T, F>
+    where
+        T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64> + Send + Sync + 'static,
+        F: Fn(&CoordCube) -> T;
+}
+
+/// Each table built for the solver alone, with `pattern_database`.
+pub(super) struct OwnTables;
+
+impl<E: Evaluator> TableSource<E> for OwnTables {
+    fn table<T, F>(
+        &self,
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        challenge: &Challenge<E>,
+        limit: Option<TableLimit>,
+    ) -> PatternDatabase<T, F>
+    where
+        T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64> + Send + Sync + 'static,
+        F: Fn(&CoordCube) -> T,
+    {
+        pattern_database(name, simplifier, allowed_moves, challenge, limit)
+    }
+}
+
+/// Tables shared with other solvers, with `shared_pattern_database`.
+pub(super) struct SharedTables;
+
+impl<E: Evaluator + PartialEq + Clone> TableSource<E> for SharedTables {
+    fn table<T, F>(
+        &self,
+        name: &str,
+        simplifier: F,
+        allowed_moves: &[Move],
+        challenge: &Challenge<E>,
+        limit: Option<TableLimit>,
+    ) -> PatternDatabase<T, F>
+    where
+        T: Eq + Hash + core::fmt::Debug + Copy + Into<u64> + TryFrom<u64> + Send + Sync + 'static,
+        F: Fn(&CoordCube) -> T,
+    {
+        shared_pattern_database(name, simplifier, allowed_moves, challenge, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple_evaluator(moves: &[Move]) -> Duration {
+        Duration::from_millis(10) * (moves.len() as u32)
+    }
+
+    lazy_static::lazy_static! {
+        static ref CORNER_ORIENTATION: PatternDatabase<u16, fn(&CoordCube) -> u16> =
+            PatternDatabase::init(
+                "corner_orientation",
+                |c| c.corner_orientation(),
+                &Move::all().collect::<Vec<_>>(),
+                &simple_evaluator,
+                None,
+            );
+    }
+
+    #[test]
+    fn has_quickcheck_generated() {
+        let cube = Cube::solved().apply_all(moves![Rp, F2, Up]);
+        assert!(CORNER_ORIENTATION.has(&cube));
+    }
+
+    #[test]
+    fn has_sune() {
+        let cube = Cube::solved().apply_all(moves![R, Up, Rp, Up, R, U2, Rp]);
+        assert!(CORNER_ORIENTATION.has(&cube));
+    }
+
+    #[test]
+    fn corner_orientations_fit() {
+        let size = CORNER_ORIENTATION.table_size();
+        assert_eq!(size.name, "corner_orientation");
+        assert!(size.states <= 2187, "{}", size.states);
+    }
+
+    #[test]
+    fn lookup_solved_is_zero() {
+        let cube = CoordCube::from(Cube::solved());
+        assert_eq!(CORNER_ORIENTATION.lookup(&cube), Some(Duration::default()));
+    }
+
+    #[quickcheck]
+    fn is_exhaustive(moves: Vec<Move>) -> bool {
+        let cube = Cube::solved().apply_all(moves);
+        CORNER_ORIENTATION.has(&cube)
+    }
+
+    lazy_static::lazy_static! {
+        static ref RELOADED: PatternDatabase<u16, fn(&CoordCube) -> u16> = {
+            let mut bytes = Vec::new();
+            CORNER_ORIENTATION.save(&mut bytes).unwrap();
+            let simplifier: fn(&CoordCube) -> u16 = |c| c.corner_orientation();
+            PatternDatabase::load("corner_orientation", simplifier, &bytes[..]).unwrap()
+        };
+    }
+
+    #[quickcheck]
+    fn loaded_matches_built(moves: Vec<Move>) -> bool {
+        let cube = CoordCube::from(Cube::solved().apply_all(moves));
+        RELOADED.lookup(&cube) == CORNER_ORIENTATION.lookup(&cube)
+    }
+
+    #[test]
+    fn loaded_has_same_entries() {
+        assert_eq!(RELOADED.map, CORNER_ORIENTATION.map);
+        assert_eq!(RELOADED.is_exhaustive(), CORNER_ORIENTATION.is_exhaustive());
+    }
+
+    #[test]
+    fn load_rejects_garbage() {
+        let loaded = PatternDatabase::<u16, _>::load(
+            "garbage",
+            |c: &CoordCube| c.corner_orientation(),
+            &b"not a table"[..],
+        );
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn load_rejects_truncated_huge_length() {
+        let bytes = [&MAGIC[..], &[1], &u64::MAX.to_le_bytes()].concat();
+        let loaded = PatternDatabase::<u16, _>::load(
+            "truncated",
+            |c: &CoordCube| c.corner_orientation(),
+            &bytes[..],
+        );
+        assert!(loaded.is_err());
+    }
+
+    fn up_only(on_missing: OnMissing) -> PatternDatabase<u16, fn(&CoordCube) -> u16> {
+        let moves = Move::all()
+            .filter(|m| m.face == Face::Up)
+            .collect::<Vec<_>>();
+        let simplifier: fn(&CoordCube) -> u16 = |c| c.corner_position();
+        PatternDatabase::init("up_only", simplifier, &moves, &simple_evaluator, None)
+            .on_missing(on_missing)
+    }
+
+    #[test]
+    #[should_panic(expected = "up_only: missing value")]
+    fn missing_value_panics() {
+        up_only(OnMissing::Panic).min_time(&CoordCube::from(cube_with_moves("R")));
+    }
+
+    #[test]
+    fn missing_value_warns() {
+        let table = up_only(OnMissing::Warn);
+        assert!(table.is_exhaustive());
+        assert_eq!(
+            table.min_time(&CoordCube::from(cube_with_moves("R"))),
+            Duration::default()
+        );
+    }
+
+    fn corner_position_to_depth(depth: usize) -> PatternDatabase<u16, fn(&CoordCube) -> u16> {
+        let simplifier: fn(&CoordCube) -> u16 = |c| c.corner_position();
+        PatternDatabase::init(
+            "corner_position",
+            simplifier,
+            &Move::all().collect::<Vec<_>>(),
+            &simple_evaluator,
+            Some(TableLimit::Depth(depth)),
+        )
+    }
+
+    #[test]
+    fn depth_limit_is_deterministic() {
+        let table = corner_position_to_depth(3);
+        assert!(!table.is_exhaustive());
+        assert_eq!(table.map, corner_position_to_depth(3).map);
+        assert_eq!(table.map.values().max(), Some(&Duration::from_millis(30)));
+    }
+
+    fn corner_orientation(cube: &CoordCube) -> u16 {
+        cube.corner_orientation()
+    }
+
+    #[test]
+    fn cache_key_depends_on_limit() {
+        let all = Move::all().collect::<Vec<_>>();
+        let key = |limit| cache_key(&corner_orientation, &all, &simple_evaluator, limit);
+        assert_ne!(key(None), key(Some(TableLimit::Depth(3))));
+        assert_ne!(
+            key(Some(TableLimit::Depth(3))),
+            key(Some(TableLimit::Time(Duration::from_nanos(3))))
+        );
+    }
+
+    #[test]
+    fn cache_key_depends_on_moves() {
+        let all = Move::all().collect::<Vec<_>>();
+        assert_ne!(
+            cache_key(&corner_orientation, &all, &simple_evaluator, None),
+            cache_key(&corner_orientation, &all[1..], &simple_evaluator, None)
+        );
+    }
+
+    #[test]
+    fn cache_key_depends_on_coordinate() {
+        let all = Move::all().collect::<Vec<_>>();
+        let corner_position = |c: &CoordCube| c.corner_position();
+        assert_ne!(
+            cache_key(&corner_orientation, &all, &simple_evaluator, None),
+            cache_key(&corner_position, &all, &simple_evaluator, None)
+        );
+    }
+
+    /// Ten milliseconds a move, and a penalty on sequences too long for `cache_key` to see.
+    #[derive(Clone, PartialEq)]
+    struct LongPenalty(Duration);
+
+    impl Evaluator for LongPenalty {
+        fn eval(&self, seq: &[Move]) -> Duration {
+            let penalty = if seq.len() > 2 {
+                self.0
+            } else {
+                Duration::default()
+            };
+            simple_evaluator(seq) + penalty
+        }
+    }
+
+    fn shared_up_only(evaluator: LongPenalty) -> PatternDatabase<u16, fn(&CoordCube) -> u16> {
+        let moves = Move::all()
+            .filter(|m| m.face == Face::Up)
+            .collect::<Vec<_>>();
+        let simplifier: fn(&CoordCube) -> u16 = |c| c.corner_position();
+        let challenge = Challenge::new(evaluator);
+        shared_pattern_database("shared_up_only", simplifier, &moves, &challenge, None)
+    }
+
+    #[test]
+    fn shared_tables_share_entries() {
+        let first = shared_up_only(LongPenalty(Duration::from_millis(1)));
+        let builds = BUILDS.with(|b| b.get());
+        let again = shared_up_only(LongPenalty(Duration::from_millis(1)));
+        assert_eq!(BUILDS.with(|b| b.get()), builds);
+        assert!(Arc::ptr_eq(&first.map, &again.map));
+    }
+
+    #[test]
+    fn shared_tables_need_equal_evaluators() {
+        let (short, long) = (
+            LongPenalty(Duration::from_millis(2)),
+            LongPenalty(Duration::from_millis(3)),
+        );
+        let corner_position = |c: &CoordCube| c.corner_position();
+        assert_eq!(
+            cache_key(&corner_position, &[], &short, None),
+            cache_key(&corner_position, &[], &long, None)
+        );
+
+        let first = shared_up_only(short);
+        let other = shared_up_only(long);
+        assert!(!Arc::ptr_eq(&first.map, &other.map));
+    }
+
+    #[test]
+    fn shared_tables_are_freed_with_their_last_user() {
+        let table = shared_up_only(LongPenalty(Duration::from_millis(4)));
+        let map = Arc::downgrade(&table.map);
+        drop(table);
+        assert!(map.upgrade().is_none());
+
+        let builds = BUILDS.with(|b| b.get());
+        shared_up_only(LongPenalty(Duration::from_millis(4)));
+        assert_eq!(BUILDS.with(|b| b.get()), builds + 1);
+    }
+}
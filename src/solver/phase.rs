@@ -0,0 +1,280 @@
+use crate::cube::coord::{CoordCube, TableSize};
+use crate::cube::subgroup::is_domino_move;
+use crate::prelude::*;
+
+use core::hash::Hash;
+use std::collections::{HashSet, VecDeque};
+
+/// The most states `Phase::can_finish` visits before assuming the phase can finish.
+const REACHABLE_CHECK_STATES: usize = 10_000;
+
+/// One stage of a `PhasedSolver`: search with `allowed_moves` until `finished_when` holds.
+///
+/// The phase's lower bound is the largest of its heuristics, or zero without any. Of equally fast
+/// solutions, the search finds the one first in `Move::index` order, whatever order the moves
+/// are given in.
+///
+/// ```
+/// use blastcube::cube::coord::CoordCube;
+/// use blastcube::prelude::*;
+/// use blastcube::solver::{Phase, PhasedSolver};
+///
+/// let oriented = |c: &CoordCube| c.edge_orientation() == 0;
+/// let solver = PhasedSolver::new(
+///     Challenge::new(|seq: &[Move]| Duration::from_millis(10) * seq.len() as u32),
+///     vec![Phase::init(Move::all(), oriented, Vec::new())],
+/// );
+///
+/// let mut solution = Vec::new();
+/// solver
+///     .solve_with(&Cube::solved().apply_str("F").unwrap(), |m| solution.push(m))
+///     .unwrap();
+/// assert_eq!(solution.len(), 1);
+/// ```
+pub struct Phase {
+    allowed_moves: Vec<Move>,
+    /// Every allowed move is a domino move, so none change either orientation.
+    domino: bool,
+    finished_when: fn(&CoordCube) -> bool,
+    heuristics: Vec<Box<dyn Heuristic>>,
+    max_moves: usize,
+}
+
+impl Phase {
+    pub fn init(
+        allowed_moves: impl IntoIterator<Item = Move>,
+        finished_when: fn(&CoordCube) -> bool,
+        heuristics: Vec<Box<dyn Heuristic>>,
+    ) -> Self {
+        let mut allowed_moves = allowed_moves.into_iter().collect::<Vec<_>>();
+        // Searched in order, which breaks ties between equally fast solutions.
+        allowed_moves.sort_by_key(Move::index);
+        Self {
+            domino: allowed_moves.iter().all(is_domino_move),
+            allowed_moves,
+            finished_when,
+            heuristics,
+            max_moves: usize::MAX,
+        }
+    }
+
+    /// Give up on the phase, as `NotSolvable`, rather than search past `moves` moves.
+    ///
+    /// Without a limit, a phase whose moves can't reach its goal searches forever unless
+    /// `can_finish` catches it, and it can only do that in small groups. Restricted moves whose
+    /// group is too big for that should set one, at least the longest the phase can need.
+    pub fn max_moves(mut self, moves: usize) -> Self {
+        self.max_moves = moves;
+        self
+    }
+
+    /// The most moves the phase searches, see `max_moves`. `usize::MAX` when unlimited.
+    pub fn move_limit(&self) -> usize {
+        self.max_moves
+    }
+
+    pub fn allowed_moves(&self) -> &[Move] {
+        &self.allowed_moves
+    }
+
+    /// `move_` applied to `cube`, skipping the orientations when the phase can't change them.
+    pub fn apply(&self, cube: &CoordCube, move_: Move) -> CoordCube {
+        let oriented = cube.corner_orientation() == 0 && cube.edge_orientation() == 0;
+        if self.domino && oriented {
+            cube.clone().apply_domino(move_)
+        } else {
+            cube.clone().apply(move_)
+        }
+    }
+
+    pub fn min_time(&self, cube: &CoordCube) -> Duration {
+        self.heuristics
+            .iter()
+            .map(|h| h.min_time(cube))
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn is_finished(&self, cube: &CoordCube) -> bool {
+        (self.finished_when)(cube)
+    }
+
+    /// Whether the allowed moves might take `cube` to the phase's goal.
+    ///
+    /// A breadth first search over the states they reach, which is only `false` when it runs out
+    /// of states without finishing. Past `REACHABLE_CHECK_STATES` it can't tell and says `true`,
+    /// leaving the search's `max_moves` to stop it. It lets small groups be refused before the
+    /// search starts.
+    pub fn can_finish(&self, cube: &CoordCube) -> bool {
+        let mut seen = HashSet::from([cube.as_u128()]);
+        let mut queue = VecDeque::from([cube.clone()]);
+        while let Some(cube) = queue.pop_front() {
+            if self.is_finished(&cube) || seen.len() > REACHABLE_CHECK_STATES {
+                return true;
+            }
+            for &move_ in &self.allowed_moves {
+                let next = self.apply(&cube, move_);
+                if seen.insert(next.as_u128()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        false
+    }
+
+    /// The sizes of the heuristics' tables, for those backed by one.
+    pub fn table_sizes(&self) -> impl Iterator<Item = TableSize> + '_ {
+        self.heuristics.iter().filter_map(|h| h.table_size())
+    }
+}
+
+/// A lower bound on the time to finish a `Phase` from a cube.
+///
+/// Searches are only optimal when it never overestimates.
+pub trait Heuristic: Sync + Send {
+    fn min_time(&self, cube: &CoordCube) -> Duration;
+
+    /// The size of the table behind it, if there is one.
+    fn table_size(&self) -> Option<TableSize> {
+        None
+    }
+}
+
+impl<T, F> Heuristic for PatternDatabase<T, F>
+where
+    T: Eq + Hash + Sync + Send + core::fmt::Debug,
+    F: Fn(&CoordCube) -> T + Sync + Send,
+{
+    fn min_time(&self, cube: &CoordCube) -> Duration {
+        PatternDatabase::min_time(self, cube)
+    }
+
+    fn table_size(&self) -> Option<TableSize> {
+        Some(PatternDatabase::table_size(self))
+    }
+}
+
+impl<F> Heuristic for F
+where
+    F: Fn(&CoordCube) -> Duration + Sync + Send,
+{
+    fn min_time(&self, cube: &CoordCube) -> Duration {
+        (self)(cube)
+    }
+}
+
+/// A cheap lower bound from which faces aren't a single color yet.
+///
+/// Turning the faces of one axis only changes the faces on the other two, so the solve needs a
+/// run of moves on another axis for as long as unsolved faces touch every axis. Each run after
+/// the first costs at least the cheapest change of axis, since the first may continue the run
+/// the search is already in.
+///
+/// Assumes a move's time only depends on the move before it, as with `BlastMachineEvaluator`.
+/// It is only admissible for phases that finish at the solved cube.
+pub struct UnsolvedFaces {
+    axis_change: Duration,
+}
+
+impl UnsolvedFaces {
+    pub fn new(evaluator: &impl Evaluator) -> Self {
+        let axis_change = Move::all()
+            .flat_map(|a| Move::all().map(move |b| (a, b)))
+            .filter(|(a, b)| !Face::same_axis(a.face, b.face))
+            .map(|(a, b)| evaluator.eval(&[a, b]).saturating_sub(evaluator.eval(&[a])))
+            .min()
+            .unwrap();
+        UnsolvedFaces { axis_change }
+    }
+
+    /// How many runs of same-axis moves it takes to solve the cube, at least.
+    fn axis_runs(cube: &Cube) -> u32 {
+        let unsolved = enum_iterator::all::<Face>()
+            .filter(|&face| {
+                Location::all()
+                    .any(|location| location.face() == face && cube.get(location) != face)
+            })
+            .collect::<Vec<_>>();
+
+        let misses_an_axis = enum_iterator::all::<Axis>()
+            .any(|axis| unsolved.iter().all(|face| face.axis() != axis));
+        match (unsolved.is_empty(), misses_an_axis) {
+            (true, _) => 0,
+            (false, true) => 1,
+            (false, false) => 2,
+        }
+    }
+}
+
+impl Heuristic for UnsolvedFaces {
+    fn min_time(&self, cube: &CoordCube) -> Duration {
+        let runs = Self::axis_runs(&cube.to_cube());
+        self.axis_change * runs.saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+
+    #[test]
+    fn min_time_is_largest_heuristic() {
+        let phase = Phase::init(
+            Move::all(),
+            CoordCube::is_solved,
+            vec![
+                Box::new(|_: &CoordCube| Duration::from_millis(10)),
+                Box::new(|_: &CoordCube| Duration::from_millis(30)),
+            ],
+        );
+        let cube = CoordCube::from(Cube::solved());
+        assert_eq!(phase.min_time(&cube), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn no_heuristics_is_zero() {
+        let phase = Phase::init(Move::all(), CoordCube::is_solved, Vec::new());
+        let cube = CoordCube::from(cube_with_moves("R"));
+        assert_eq!(phase.min_time(&cube), Duration::default());
+    }
+
+    #[test]
+    fn can_finish_within_group() {
+        let up_down = Move::all().filter(|m| matches!(m.face, Face::Up | Face::Down));
+        let phase = Phase::init(up_down, CoordCube::is_solved, Vec::new());
+        assert!(phase.can_finish(&CoordCube::from(cube_with_moves("U D2"))));
+        assert!(!phase.can_finish(&CoordCube::from(cube_with_moves("R"))));
+    }
+
+    #[test]
+    fn large_groups_are_assumed_to_finish() {
+        let phase = Phase::init(Move::all(), |_: &CoordCube| false, Vec::new());
+        assert!(phase.can_finish(&CoordCube::from(Cube::solved())));
+    }
+
+    #[test]
+    fn unsolved_faces_bounds() {
+        let heuristic = UnsolvedFaces::new(&BlastMachineEvaluator::default());
+        let min_time =
+            |scramble: &str| heuristic.min_time(&CoordCube::from(cube_with_moves(scramble)));
+
+        assert_eq!(min_time("F F'"), Duration::default());
+        assert_eq!(min_time("R L'"), Duration::default());
+        assert_eq!(min_time("R U"), Duration::from_millis(10));
+    }
+
+    #[quickcheck]
+    fn unsolved_faces_is_admissible(scramble: Vec<Move>, last: Move) -> bool {
+        let heuristic = UnsolvedFaces::new(&BlastMachineEvaluator::default());
+        let cube = CoordCube::from(Cube::solved().apply_all(scramble.clone()));
+
+        // Undoing the scramble, after a search has already played `last`.
+        let mut solve = vec![last];
+        solve.extend(Move::invert_sequence(&scramble));
+        let evaluator = BlastMachineEvaluator::default();
+        let remaining = evaluator.eval(&solve) - evaluator.eval(&[last]);
+
+        heuristic.min_time(&cube) <= remaining
+    }
+}
@@ -0,0 +1,955 @@
+use crate::cube::coord::{CoordCube, TableSize};
+use crate::prelude::*;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Solves a cube through a sequence of phases. Each phase is an IDA* search, restricted to its
+/// own moves, from wherever the previous phase finished.
+pub struct PhasedSolver<E: Evaluator> {
+    challenge: Challenge<E>,
+    phases: Vec<Phase>,
+    first_phase_candidates: usize,
+}
+
+impl<E: Evaluator> PhasedSolver<E> {
+    pub fn new(challenge: Challenge<E>, phases: Vec<Phase>) -> Self {
+        PhasedSolver {
+            challenge,
+            phases,
+            first_phase_candidates: 1,
+        }
+    }
+
+    /// Finish the cheapest `count` solutions to the first phase, rather than only the first one
+    /// found, and keep whichever makes the cheapest overall solution. The first phase's moves
+    /// can't be emitted until every candidate has been finished.
+    pub fn first_phase_candidates(mut self, count: usize) -> Self {
+        assert!(count > 0, "Need at least one first phase candidate");
+        self.first_phase_candidates = count;
+        self
+    }
+
+    pub fn challenge(&self) -> &Challenge<E> {
+        &self.challenge
+    }
+
+    /// The sizes of every phase's heuristic tables.
+    pub fn table_sizes(&self) -> Vec<TableSize> {
+        self.phases.iter().flat_map(Phase::table_sizes).collect()
+    }
+
+    /// Solve the cube, emitting each phase's moves as soon as that phase is found. Stops at
+    /// the first phase that is `NotSolvable`, after emitting the phases before it.
+    pub fn solve_with(&self, cube: &Cube, mut emit: impl FnMut(Move)) -> Result<(), NotSolvable> {
+        let result = self.try_solve_with(cube, |m| {
+            emit(m);
+            Ok::<_, core::convert::Infallible>(())
+        });
+        match result {
+            Ok(result) => result,
+            Err(never) => match never {},
+        }
+    }
+
+    /// The whole solution, or `NotSolvable` if a phase's moves can't reach its goal.
+    pub fn try_solve(&self, cube: &Cube) -> Result<Vec<Move>, NotSolvable> {
+        self.solve_phased(cube).map(|solution| solution.moves)
+    }
+
+    /// The whole solution along with where each phase ends in it.
+    pub fn solve_phased(&self, cube: &Cube) -> Result<PhasedSolution, NotSolvable> {
+        let mut moves = Vec::new();
+        let mut phase_boundaries = Vec::new();
+        let result = self.solve_phases(
+            cube,
+            &AtomicBool::new(false),
+            |m| {
+                moves.push(m);
+                Ok::<_, core::convert::Infallible>(())
+            },
+            |end| phase_boundaries.push(end),
+        );
+        match result {
+            Ok(result) => result.map(|()| PhasedSolution {
+                moves,
+                phase_boundaries,
+            }),
+            Err(never) => match never {},
+        }
+    }
+
+    /// Like `solve_with`, but stops at the first move `emit` fails on, with the outer error.
+    ///
+    /// A solved cube emits nothing without searching, as does any phase whose goal the cube has
+    /// already reached, so a cube in the domino group skips straight past Kociemba's first phase.
+    pub fn try_solve_with<Err>(
+        &self,
+        cube: &Cube,
+        emit: impl FnMut(Move) -> Result<(), Err>,
+    ) -> Result<Result<(), NotSolvable>, Err> {
+        self.try_solve_cancellable(cube, &AtomicBool::new(false), emit, |_| {})
+    }
+
+    /// Like `try_solve_with`, but abandoning the search as soon as `cancel` is set. Nothing more
+    /// is emitted after that, and the result says nothing about the cube, since whoever set it
+    /// has stopped listening.
+    ///
+    /// `phase_done` is called after each phase's moves are emitted, with how many have been
+    /// emitted in all.
+    pub(super) fn try_solve_cancellable<Err>(
+        &self,
+        cube: &Cube,
+        cancel: &AtomicBool,
+        mut emit: impl FnMut(Move) -> Result<(), Err>,
+        phase_done: impl FnMut(usize),
+    ) -> Result<Result<(), NotSolvable>, Err> {
+        let level = self.challenge.log_level();
+        log::log!(level, "Solving");
+        let start = std::time::Instant::now();
+
+        let mut emitted = 0;
+        let result = self.solve_phases(
+            cube,
+            cancel,
+            |m| {
+                emitted += 1;
+                emit(m)
+            },
+            phase_done,
+        )?;
+        if cancel.load(Ordering::Relaxed) {
+            log::debug!("Cancelled after {} moves", emitted);
+            return Ok(Ok(()));
+        }
+        if let Err(e) = result {
+            log::log!(level, "Gave up after {} moves: {}", emitted, e);
+            return Ok(Err(e));
+        }
+
+        log::log!(
+            level,
+            "Solved in {} moves, took {:?}",
+            emitted,
+            start.elapsed()
+        );
+        Ok(Ok(()))
+    }
+
+    /// Emits the solution, with the outer error from `emit` and the inner when a phase can't
+    /// finish. After each phase, `phase_done` is told how many moves have been emitted.
+    fn solve_phases<Err>(
+        &self,
+        cube: &Cube,
+        cancel: &AtomicBool,
+        mut emit: impl FnMut(Move) -> Result<(), Err>,
+        mut phase_done: impl FnMut(usize),
+    ) -> Result<Result<(), NotSolvable>, Err> {
+        if *cube == Cube::solved() {
+            self.phases.iter().for_each(|_| phase_done(0));
+            return Ok(Ok(()));
+        }
+
+        if self.first_phase_candidates > 1 {
+            let (solution, ends) =
+                match self.solve_from_candidates(cube, self.first_phase_candidates, cancel) {
+                    Ok(solution) => solution,
+                    Err(e) => return Ok(Err(e)),
+                };
+            let mut start = 0;
+            for end in ends {
+                for &m in &solution[start..end] {
+                    emit(m)?;
+                }
+                phase_done(end);
+                start = end;
+            }
+            return Ok(Ok(()));
+        }
+
+        let mut solution = Vec::new();
+        for (i, phase) in self.phases.iter().enumerate() {
+            let reached = CoordCube::from(cube.clone().apply_all(solution.iter().copied()));
+            if phase.is_finished(&reached) {
+                log::debug!("Phase {} already finished", i);
+                phase_done(solution.len());
+                continue;
+            }
+            if !phase.can_finish(&reached) {
+                return Ok(Err(NotSolvable { phase: i }));
+            }
+
+            let solved_len = solution.len();
+            let parallel = cfg!(feature = "parallel");
+            let search = self.solve_within(cube, phase, solution, parallel, Duration::MAX, cancel);
+            solution = match search {
+                Some(solution) => solution,
+                None => return Ok(Err(NotSolvable { phase: i })),
+            };
+            log::debug!(
+                "Phase {} path: {}",
+                i,
+                Algorithm::new(solution[solved_len..].to_vec())
+            );
+            for m in &solution[solved_len..] {
+                emit(*m)?;
+            }
+            phase_done(solution.len());
+        }
+        Ok(Ok(()))
+    }
+
+    /// Whether `solution` is known to be the fastest way to solve `cube`.
+    ///
+    /// Every solution passes through each phase's goal on its way to solved, so a phase that
+    /// allows every move bounds the whole solve with its heuristics. `Optimal` is only asserted
+    /// when the solution takes no longer than the largest of those bounds. Restricted phases say
+    /// nothing about solutions using other moves, so they can't prove anything.
+    ///
+    /// This is conservative, an optimal solution is still `BestFound` when the heuristics are
+    /// loose, as they are for anything but the shortest solves.
+    pub fn quality(&self, cube: &Cube, solution: &[Move]) -> SolutionQuality {
+        let coord = CoordCube::from(cube.clone());
+        let all_moves = Move::all().count();
+        let lower_bound = self
+            .phases
+            .iter()
+            .filter(|phase| phase.allowed_moves().len() == all_moves)
+            .map(|phase| phase.min_time(&coord))
+            .max()
+            .unwrap_or_default();
+
+        let evaluator = &self.challenge.evaluator;
+        if evaluator.eval(solution) <= evaluator.eval(&[]) + lower_bound {
+            SolutionQuality::Optimal
+        } else {
+            SolutionQuality::BestFound
+        }
+    }
+
+    /// Finish each of the `count` cheapest solutions to the first phase, keeping the cheapest,
+    /// along with where each phase ends in it. Later candidates give up once they can't beat the
+    /// best so far, or when a later phase can't finish from where they leave the cube.
+    fn solve_from_candidates(
+        &self,
+        cube: &Cube,
+        count: usize,
+        cancel: &AtomicBool,
+    ) -> Result<(Vec<Move>, Vec<usize>), NotSolvable> {
+        let parallel = cfg!(feature = "parallel");
+        let (first, rest) = self.phases.split_first().unwrap();
+        if !first.can_finish(&CoordCube::from(cube.clone())) {
+            return Err(NotSolvable { phase: 0 });
+        }
+
+        let mut best: Option<(Duration, Vec<Move>, Vec<usize>)> = None;
+        let mut unsolvable = None;
+        for candidate in self.first_solutions(cube, first, count, cancel) {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            log::debug!("Phase 0 candidate: {}", Algorithm::new(candidate.clone()));
+            let limit = best.as_ref().map_or(Duration::MAX, |(time, ..)| *time);
+            let mut ends = vec![candidate.len()];
+            let solution = rest
+                .iter()
+                .enumerate()
+                .try_fold(candidate, |solution, (i, phase)| {
+                    let reached = CoordCube::from(cube.clone().apply_all(solution.iter().copied()));
+                    let solved = if phase.can_finish(&reached) {
+                        self.solve_within(cube, phase, solution, parallel, limit, cancel)
+                    } else {
+                        None
+                    };
+                    // Running out of moves or time look alike, so only the first candidate,
+                    // which has no time limit, can tell that a phase can't finish.
+                    if solved.is_none() && limit == Duration::MAX {
+                        unsolvable = Some(NotSolvable { phase: i + 1 });
+                    }
+                    ends.extend(solved.as_ref().map(Vec::len));
+                    solved
+                });
+            if let Some(solution) = solution {
+                let time = self.challenge.evaluator.eval(&solution);
+                if time < limit {
+                    best = Some((time, solution, ends));
+                }
+            }
+        }
+        match best {
+            Some((_, solution, ends)) => Ok((solution, ends)),
+            // No candidates at all means the first phase ran out of moves.
+            None => Err(unsolvable.unwrap_or(NotSolvable { phase: 0 })),
+        }
+    }
+
+    /// The `count` cheapest solutions to `phase`, or fewer if there aren't that many. `cancel`
+    /// is checked between deepenings, which can each take a while.
+    fn first_solutions(
+        &self,
+        cube: &Cube,
+        phase: &Phase,
+        count: usize,
+        cancel: &AtomicBool,
+    ) -> Vec<Vec<Move>> {
+        let cube = CoordCube::from(cube.clone());
+
+        let start_time = self.challenge.evaluator.eval(&[]);
+        let mut bound = start_time;
+        loop {
+            let mut found = Vec::new();
+            let next_bound = self.collect_solutions(
+                nanos(bound),
+                &cube,
+                &mut Vec::new(),
+                start_time,
+                phase,
+                &mut found,
+            );
+            let cancelled = cancel.load(Ordering::Relaxed);
+            if found.len() >= count || next_bound == Duration::MAX || cancelled {
+                found.sort_by_cached_key(|solution| self.challenge.evaluator.eval(solution));
+                found.truncate(count);
+                return found;
+            }
+            bound = next_bound;
+        }
+    }
+
+    /// Like `find_solution` from the start of the phase, but collecting every solution within
+    /// `bound` into `found`. Returns the cheapest time beyond `bound`.
+    fn collect_solutions(
+        &self,
+        bound: u64,
+        cube: &CoordCube,
+        move_stack: &mut Vec<Move>,
+        stack_time: Duration,
+        phase: &Phase,
+        found: &mut Vec<Vec<Move>>,
+    ) -> Duration {
+        match self.check_node(
+            bound,
+            cube,
+            move_stack,
+            stack_time,
+            phase,
+            phase.move_limit(),
+        ) {
+            Some(Search::Found(moves)) => {
+                found.push(moves);
+                return Duration::MAX;
+            }
+            Some(Search::NotFound(time)) => return time,
+            None => {}
+        }
+
+        let mut best = Duration::MAX;
+        for move_ in next_moves(phase, move_stack) {
+            let time = self
+                .challenge
+                .evaluator
+                .eval_push(move_stack, stack_time, move_);
+            move_stack.push(move_);
+            let cube = phase.apply(cube, move_);
+            let sub = self.collect_solutions(bound, &cube, move_stack, time, phase, found);
+            move_stack.pop();
+            best = core::cmp::min(best, sub);
+        }
+        best
+    }
+
+    /// The fastest way to finish `phase` after `prefix`, `None` if it would take `limit` or
+    /// longer or more than the phase's `max_moves`, or once `cancel` is set.
+    fn solve_within(
+        &self,
+        cube: &Cube,
+        phase: &Phase,
+        mut prefix: Vec<Move>,
+        parallel: bool,
+        limit: Duration,
+        cancel: &AtomicBool,
+    ) -> Option<Vec<Move>> {
+        let cube = CoordCube::from(cube.clone().apply_all(prefix.clone()));
+
+        let prefix_time = self.challenge.evaluator.eval(&prefix);
+        let max_len = prefix.len().saturating_add(phase.move_limit());
+        let mut best_time = prefix_time;
+        while best_time < limit {
+            log::debug!("Searching <= {:?}", best_time);
+            let bound = SharedBound {
+                nanos: nanos(best_time),
+                root_len: prefix.len(),
+                found_first: AtomicUsize::new(usize::MAX),
+                cancel,
+            };
+            let search = if parallel {
+                self.find_solution_parallel(&bound, &cube, &prefix, prefix_time, phase, max_len)
+            } else {
+                self.find_solution(&bound, &cube, &mut prefix, prefix_time, phase, max_len)
+            };
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            match search {
+                Search::Found(moves) => return Some(moves),
+                Search::NotFound(next_best_time) => {
+                    best_time = next_best_time;
+                }
+            }
+        }
+        None
+    }
+
+    /// Search below `cube` for a solution within `bound`. Finding one lets searches sharing it
+    /// under later first moves stop early.
+    ///
+    /// Moves are tried in `Move::index` order, so of the equally fast solutions within `bound`
+    /// the lexicographically smallest is found.
+    fn find_solution(
+        &self,
+        bound: &SharedBound,
+        cube: &CoordCube,
+        move_stack: &mut Vec<Move>,
+        stack_time: Duration,
+        phase: &Phase,
+        max_len: usize,
+    ) -> Search {
+        if let Some(search) =
+            self.check_shared_node(bound, cube, move_stack, stack_time, phase, max_len)
+        {
+            return search;
+        }
+
+        let mut best = Duration::MAX;
+        for move_ in next_moves(phase, move_stack) {
+            let time = self
+                .challenge
+                .evaluator
+                .eval_push(move_stack, stack_time, move_);
+            move_stack.push(move_);
+            let cube = phase.apply(cube, move_);
+            let sub = self.find_solution(bound, &cube, move_stack, time, phase, max_len);
+            move_stack.pop();
+
+            match sub {
+                // Every cheaper bound was already searched without success, so any solution
+                // within this one is as fast as the siblings could find, and theirs come later.
+                Search::Found(moves) => return Search::Found(moves),
+                Search::NotFound(t) => best = core::cmp::min(best, t),
+            }
+        }
+        Search::NotFound(best)
+    }
+
+    /// Like `find_solution`, but searching each first move on its own thread.
+    fn find_solution_parallel(
+        &self,
+        bound: &SharedBound,
+        cube: &CoordCube,
+        move_stack: &[Move],
+        stack_time: Duration,
+        phase: &Phase,
+        max_len: usize,
+    ) -> Search {
+        if let Some(search) =
+            self.check_shared_node(bound, cube, move_stack, stack_time, phase, max_len)
+        {
+            return search;
+        }
+
+        let subs = std::thread::scope(|s| {
+            let handles = next_moves(phase, move_stack)
+                .map(|move_| {
+                    s.spawn(move || {
+                        let time = self
+                            .challenge
+                            .evaluator
+                            .eval_push(move_stack, stack_time, move_);
+                        let mut move_stack = move_stack.to_vec();
+                        move_stack.push(move_);
+                        let cube = phase.apply(cube, move_);
+                        self.find_solution(bound, &cube, &mut move_stack, time, phase, max_len)
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        // The threads may finish in any order, but the first move's solution is still preferred.
+        let mut best = Duration::MAX;
+        for sub in subs {
+            match sub {
+                Search::Found(moves) => return Search::Found(moves),
+                Search::NotFound(t) => best = core::cmp::min(best, t),
+            }
+        }
+        Search::NotFound(best)
+    }
+
+    /// `check_node` against a bound shared between searches.
+    fn check_shared_node(
+        &self,
+        bound: &SharedBound,
+        cube: &CoordCube,
+        move_stack: &[Move],
+        stack_time: Duration,
+        phase: &Phase,
+        max_len: usize,
+    ) -> Option<Search> {
+        if bound.cancel.load(Ordering::Relaxed) {
+            // Unwind as quickly as possible, the result is ignored.
+            return Some(Search::NotFound(Duration::MAX));
+        }
+        let first = move_stack.get(bound.root_len).map_or(0, Move::index);
+        if first > bound.found_first.load(Ordering::Relaxed) {
+            // An earlier first move has a solution, which wins any tie with this one.
+            return Some(Search::NotFound(Duration::MAX));
+        }
+
+        let search = self.check_node(bound.nanos, cube, move_stack, stack_time, phase, max_len);
+        if let Some(Search::Found(_)) = search {
+            bound.found_first.fetch_min(first, Ordering::Relaxed);
+        }
+        search
+    }
+
+    /// The result of a node that doesn't need its children searched.
+    fn check_node(
+        &self,
+        bound: u64,
+        cube: &CoordCube,
+        move_stack: &[Move],
+        stack_time: Duration,
+        phase: &Phase,
+        max_len: usize,
+    ) -> Option<Search> {
+        #[cfg(test)]
+        NODES.with(|n| n.set(n.get() + 1));
+
+        if move_stack.len() > max_len {
+            // Nothing is found past the phase's last move, however long the bound.
+            return Some(Search::NotFound(Duration::MAX));
+        }
+
+        let min_time = phase.min_time(cube);
+        let this_time = stack_time + min_time;
+        if nanos(this_time) > bound {
+            return Some(Search::NotFound(this_time));
+        }
+
+        if min_time == Duration::default() && phase.is_finished(cube) {
+            return Some(Search::Found(move_stack.to_vec()));
+        }
+
+        None
+    }
+}
+
+fn next_moves<'p>(phase: &'p Phase, move_stack: &[Move]) -> impl Iterator<Item = Move> + 'p {
+    let last_move = move_stack.last().cloned();
+    phase
+        .allowed_moves()
+        .iter()
+        .copied()
+        .filter(move |move_| match last_move {
+            None => true,
+            Some(m) => move_.could_follow(&m),
+        })
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// How far a solution can be trusted, see `PhasedSolver::quality`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SolutionQuality {
+    /// No solution is faster under the challenge's evaluator.
+    Optimal,
+    /// The fastest solution found, but a faster one may exist.
+    BestFound,
+}
+
+/// A solution split into the moves of each phase, see `PhasedSolver::solve_phased`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PhasedSolution {
+    pub moves: Vec<Move>,
+    /// For each phase, how many of `moves` it takes to finish it. A phase the cube had already
+    /// finished ends where the one before it did.
+    pub phase_boundaries: Vec<usize>,
+}
+
+/// A phase whose moves can't take the cube to its goal, so searching would never end.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NotSolvable {
+    pub phase: usize,
+}
+
+impl core::fmt::Display for NotSolvable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "phase {} can't reach its goal with its moves",
+            self.phase
+        )
+    }
+}
+
+impl std::error::Error for NotSolvable {}
+
+#[cfg(test)]
+thread_local! {
+    /// Nodes visited by `find_solution` on this thread.
+    pub(super) static NODES: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+}
+
+/// The time in nanoseconds that searches sharing it have to beat, and whether they've been asked
+/// to stop.
+struct SharedBound<'c> {
+    nanos: u64,
+    /// How many moves were played before the search, so `move_stack[root_len]` is the first move
+    /// it tried.
+    root_len: usize,
+    /// The smallest `Move::index` of a first move that has led to a solution, `usize::MAX` before
+    /// any has.
+    found_first: AtomicUsize,
+    cancel: &'c AtomicBool,
+}
+
+enum Search {
+    NotFound(Duration),
+    Found(Vec<Move>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blast_machine_evaluator::BlastMachineEvaluator;
+    use crate::cube::subgroup::domino_moves;
+
+    fn edges_oriented(cube: &CoordCube) -> bool {
+        cube.edge_orientation() == 0
+    }
+
+    #[test]
+    fn three_phase_reduction() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let g1_moves = Move::all().filter(|m| {
+            !matches!(m.face, Face::Front | Face::Back) || m.direction == Direction::Double
+        });
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![
+                Phase::init(Move::all(), edges_oriented, Vec::new()),
+                Phase::init(g1_moves, CoordCube::is_domino, Vec::new()),
+                Phase::init(domino_moves(), CoordCube::is_solved, Vec::new()),
+            ],
+        );
+
+        let mut solution = Vec::new();
+        solver
+            .solve_with(&cube_with_moves("U2 R' F'"), |m| solution.push(m))
+            .unwrap();
+
+        assert_eq!(solution, moves![F, R, U2]);
+    }
+
+    #[test]
+    fn solved_cube_ends_every_phase_at_the_start() {
+        let solver = PhasedSolver::new(
+            Challenge::new(BlastMachineEvaluator::default()),
+            vec![Phase::init(Move::all(), |c| !c.is_solved(), Vec::new())],
+        );
+        let solution = solver.solve_phased(&Cube::solved()).unwrap();
+        assert_eq!(solution.moves, vec![]);
+        assert_eq!(solution.phase_boundaries, vec![0]);
+    }
+
+    #[test]
+    fn candidates_report_phase_boundaries() {
+        let cube = cube_with_moves("U D' R");
+        let solution = up_down_only()
+            .first_phase_candidates(3)
+            .solve_phased(&cube)
+            .unwrap();
+
+        let [domino_len, len] = solution.phase_boundaries[..] else {
+            panic!("{:?}", solution.phase_boundaries);
+        };
+        assert_eq!(len, solution.moves.len());
+        let domino = cube.apply_all(solution.moves[..domino_len].iter().copied());
+        assert!(CoordCube::from(domino).is_domino());
+    }
+
+    fn up_down_only() -> PhasedSolver<BlastMachineEvaluator> {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let up_down = Move::all().filter(|m| matches!(m.face, Face::Up | Face::Down));
+        PhasedSolver::new(
+            challenge,
+            vec![
+                Phase::init(Move::all(), CoordCube::is_domino, Vec::new()),
+                Phase::init(up_down, CoordCube::is_solved, Vec::new()),
+            ],
+        )
+    }
+
+    #[test]
+    fn restricted_moves_are_not_solvable() {
+        let solver = up_down_only();
+        assert_eq!(
+            solver.try_solve(&cube_with_moves("R2")),
+            Err(NotSolvable { phase: 1 })
+        );
+        assert_eq!(
+            solver
+                .first_phase_candidates(2)
+                .try_solve(&cube_with_moves("R2 U")),
+            Err(NotSolvable { phase: 1 })
+        );
+    }
+
+    fn per_move(seq: &[Move]) -> Duration {
+        Duration::from_millis(10) * seq.len() as u32
+    }
+
+    #[test]
+    fn ties_go_to_the_smallest_indexes() {
+        let challenge = Challenge::new(per_move as fn(&[Move]) -> Duration);
+        // F and F' both orient the edges F flipped.
+        let backwards = Move::all().collect::<Vec<_>>().into_iter().rev();
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![Phase::init(backwards, edges_oriented, Vec::new())],
+        );
+
+        let cube = cube_with_moves("F");
+        assert_eq!(solver.try_solve(&cube), Ok(moves![F]));
+        let never = AtomicBool::new(false);
+        let phase = &solver.phases[0];
+        let parallel = solver.solve_within(&cube, phase, Vec::new(), true, Duration::MAX, &never);
+        assert_eq!(parallel, Some(moves![F]));
+    }
+
+    /// <R, U> is far too big for `Phase::can_finish` to rule anything out.
+    fn right_up_only(max_moves: usize) -> PhasedSolver<fn(&[Move]) -> Duration> {
+        let challenge = Challenge::new(per_move as fn(&[Move]) -> Duration);
+        let right_up = Move::all().filter(|m| matches!(m.face, Face::Right | Face::Up));
+        PhasedSolver::new(
+            challenge,
+            vec![Phase::init(right_up, CoordCube::is_solved, Vec::new()).max_moves(max_moves)],
+        )
+    }
+
+    #[test]
+    fn max_moves_stops_large_restricted_groups() {
+        let cube = cube_with_moves("F");
+        assert!(right_up_only(6).phases[0].can_finish(&CoordCube::from(cube.clone())));
+        assert_eq!(
+            right_up_only(6).try_solve(&cube),
+            Err(NotSolvable { phase: 0 })
+        );
+        assert_eq!(
+            right_up_only(6).first_phase_candidates(2).try_solve(&cube),
+            Err(NotSolvable { phase: 0 })
+        );
+    }
+
+    #[test]
+    fn max_moves_allows_solutions_within_it() {
+        let cube = cube_with_moves("R U R' U'");
+        assert_eq!(right_up_only(4).try_solve(&cube), Ok(moves![U, R, Up, Rp]));
+        assert_eq!(
+            right_up_only(3).try_solve(&cube),
+            Err(NotSolvable { phase: 0 })
+        );
+    }
+
+    #[test]
+    fn try_solve_with_reports_not_solvable() {
+        let result = up_down_only().try_solve_with(&cube_with_moves("R2"), |_| Ok::<_, ()>(()));
+        assert_eq!(result, Ok(Err(NotSolvable { phase: 1 })));
+    }
+
+    #[test]
+    fn cancelled_solves_emit_nothing() {
+        let cancel = AtomicBool::new(true);
+        let mut emitted = Vec::new();
+        let cube = cube_with_moves("U D'");
+        let result = up_down_only().try_solve_cancellable(
+            &cube,
+            &cancel,
+            |m| {
+                emitted.push(m);
+                Ok::<_, ()>(())
+            },
+            |_| {},
+        );
+        assert_eq!(result, Ok(Ok(())));
+        assert_eq!(emitted, vec![]);
+    }
+
+    #[test]
+    fn restricted_moves_solve_their_own_group() {
+        let cube = cube_with_moves("U D'");
+        assert_eq!(up_down_only().try_solve(&cube), Ok(moves![Up, D]));
+    }
+
+    #[test]
+    fn not_solvable_stops_emitting() {
+        let mut solution = Vec::new();
+        let result = up_down_only().solve_with(&cube_with_moves("R2"), |m| solution.push(m));
+        assert_eq!(result, Err(NotSolvable { phase: 1 }));
+        assert_eq!(solution, vec![]);
+    }
+
+    fn never_searched(_: &CoordCube) -> Duration {
+        panic!("Searched a phase that was already finished")
+    }
+
+    fn domino_reduction() -> PhasedSolver<BlastMachineEvaluator> {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        PhasedSolver::new(
+            challenge,
+            vec![
+                Phase::init(
+                    Move::all(),
+                    CoordCube::is_domino,
+                    vec![Box::new(never_searched)],
+                ),
+                Phase::init(domino_moves(), CoordCube::is_solved, Vec::new()),
+            ],
+        )
+    }
+
+    #[test]
+    fn solved_is_not_searched() {
+        let solver = PhasedSolver::new(
+            Challenge::new(BlastMachineEvaluator::default()),
+            vec![Phase::init(
+                Move::all(),
+                CoordCube::is_solved,
+                vec![Box::new(never_searched)],
+            )],
+        );
+
+        let mut solution = Vec::new();
+        solver
+            .solve_with(&Cube::solved(), |m| solution.push(m))
+            .unwrap();
+        assert_eq!(solution, vec![]);
+    }
+
+    #[test]
+    fn domino_cube_skips_first_phase() {
+        let cube = cube_with_moves("R2 U D2 L2");
+        let mut solution = Vec::new();
+        domino_reduction()
+            .solve_with(&cube, |m| solution.push(m))
+            .unwrap();
+
+        assert!(solution.len() <= 4, "{:?}", solution);
+        assert_eq!(cube.apply_all(solution), Cube::solved());
+    }
+
+    #[test]
+    fn failing_emit_stops_solving() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![
+                Phase::init(Move::all(), edges_oriented, Vec::new()),
+                Phase::init(
+                    Move::all(),
+                    |_| panic!("Searched after emit failed"),
+                    Vec::new(),
+                ),
+            ],
+        );
+
+        let mut emitted = Vec::new();
+        let result = solver.try_solve_with(&cube_with_moves("F"), |m| {
+            emitted.push(m);
+            Err("receiver dropped")
+        });
+
+        assert_eq!(result, Err("receiver dropped"));
+        assert_eq!(emitted.len(), 1);
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![Phase::init(Move::all(), edges_oriented, Vec::new())],
+        );
+
+        let never = AtomicBool::new(false);
+        for scramble in ["F", "R U F", "F B' L", "U2 R' F' D B"] {
+            let cube = cube_with_moves(scramble);
+            let phase = &solver.phases[0];
+            let solve = |parallel| {
+                solver
+                    .solve_within(&cube, phase, Vec::new(), parallel, Duration::MAX, &never)
+                    .unwrap()
+            };
+            let sequential = solve(false);
+            let parallel = solve(true);
+
+            let eval = |moves: &[Move]| solver.challenge.evaluator.eval(moves);
+            assert_eq!(eval(&parallel), eval(&sequential), "{}", scramble);
+            assert!(edges_oriented(&CoordCube::from(cube.apply_all(parallel))));
+        }
+    }
+
+    #[test]
+    fn quality_compares_against_lower_bound() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        // Any unsolved cube needs at least one move, which takes at least 10ms.
+        let one_move = |c: &CoordCube| {
+            if c.is_solved() {
+                Duration::default()
+            } else {
+                Duration::from_millis(10)
+            }
+        };
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![Phase::init(
+                Move::all(),
+                CoordCube::is_solved,
+                vec![Box::new(one_move)],
+            )],
+        );
+
+        let quality = |scramble: &str| {
+            let cube = cube_with_moves(scramble);
+            let mut solution = Vec::new();
+            solver.solve_with(&cube, |m| solution.push(m)).unwrap();
+            solver.quality(&cube, &solution)
+        };
+        assert_eq!(quality("F"), SolutionQuality::Optimal);
+        assert_eq!(quality("F R"), SolutionQuality::BestFound);
+    }
+
+    #[test]
+    fn restricted_phases_prove_nothing() {
+        let challenge = Challenge::new(BlastMachineEvaluator::default());
+        let solver = PhasedSolver::new(
+            challenge,
+            vec![Phase::init(
+                domino_moves(),
+                CoordCube::is_solved,
+                vec![Box::new(|_: &CoordCube| Duration::from_secs(1))],
+            )],
+        );
+
+        let cube = cube_with_moves("U");
+        assert_eq!(
+            solver.quality(&cube, &["U'".parse().unwrap()]),
+            SolutionQuality::BestFound
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::cube::coord::CoordCube;
+use crate::prelude::*;
+use crate::solver::pattern_database::pattern_database;
+
+use std::sync::Arc;
+
+/// Solves a cube's corners as a 2x2x2 `PocketCube`, ignoring its edges.
+///
+/// Only R, U and F turns are searched, which keep the DBL corner in place as WCA pocket cube
+/// scrambles do. Cubes with that corner moved have their colours swapped to put it home first,
+/// so their solutions end on a whole-cube rotation of solved.
+pub struct PocketSolver<E: Evaluator> {
+    phased: PhasedSolver<E>,
+}
+
+impl<E: Evaluator> Solver<E> for PocketSolver<E> {
+    fn init(challenge: Challenge<E>) -> Self {
+        CoordCube::init_table_logging_at(challenge.log_level());
+
+        let moves = Move::all()
+            .filter(|m| matches!(m.face, Face::Right | Face::Up | Face::Front))
+            .collect::<Vec<_>>();
+        let heuristics: Vec<Box<dyn Heuristic>> = vec![
+            Box::new(pattern_database(
+                "pocket_corner_orientation",
+                |c| c.corner_orientation(),
+                &moves,
+                &challenge,
+                None,
+            )),
+            Box::new(pattern_database(
+                "pocket_corner_position",
+                |c| c.corner_position(),
+                &moves,
+                &challenge,
+                None,
+            )),
+        ];
+        let phase = Phase::init(moves, CoordCube::corners_solved, heuristics);
+
+        PocketSolver {
+            phased: PhasedSolver::new(challenge, vec![phase]),
+        }
+    }
+
+    fn solve(self: &Arc<Self>, cube: Cube) -> Box<dyn Iterator<Item = Move>> {
+        Box::new(self.solve_blocking(cube).into_iter())
+    }
+
+    fn solve_blocking(self: &Arc<Self>, cube: Cube) -> Vec<Move> {
+        self.solve_pocket(PocketCube::from(cube))
+            .expect("R, U and F turns solve any pocket cube with DBL home")
+    }
+}
+
+impl<E: Evaluator> PocketSolver<E> {
+    /// The solution, which may leave the cube as a whole-cube rotation of solved when the DBL
+    /// corner isn't home, see `PocketCube::with_back_down_left_home`.
+    pub fn solve_pocket(&self, cube: PocketCube) -> Result<Vec<Move>, NotSolvable> {
+        self.phased
+            .try_solve(&cube.with_back_down_left_home().to_cube())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solver() -> PocketSolver<fn(&[Move]) -> Duration> {
+        PocketSolver::init(Challenge::new(|seq: &[Move]| {
+            Duration::from_millis(10) * seq.len() as u32
+        }))
+    }
+
+    #[test]
+    fn solves_within_gods_number() {
+        let solver = solver();
+
+        let scramble = Move::parse_sequence("R U2 F' R2 U' F R' U F2 R U' F' U2 R").unwrap();
+        let cube = PocketCube::solved().apply_all(scramble);
+        let solution = solver.solve_pocket(cube).unwrap();
+
+        // Every pocket cube is solvable in 11 half turn metric moves.
+        assert!(solution.len() <= 11, "{:?}", solution);
+        assert!(cube.apply_all(solution).is_solved());
+    }
+
+    #[test]
+    fn solves_moved_back_down_left() {
+        let solver = Arc::new(solver());
+        for scramble in ["L", "D", "B"] {
+            let cube = cube_with_moves(scramble);
+            let solution = solver.solve_blocking(cube.clone());
+            assert_eq!(solution.len(), 1, "{}: {:?}", scramble, solution);
+            assert!(PocketCube::from(cube).apply_all(solution).is_solved());
+        }
+    }
+}
@@ -1,5 +1,75 @@
+use crate::blast_machine_evaluator::BlastMachineEvaluator;
 use crate::prelude::*;
 
+use std::sync::Arc;
+
+lazy_static::lazy_static! {
+    /// One `Kociemba` for every test, since building its tables takes a while.
+    pub static ref KOCIEMBA: Arc<Kociemba<BlastMachineEvaluator>> =
+        Arc::new(Kociemba::init(Challenge::new(BlastMachineEvaluator::default())));
+}
+
 pub fn cube_with_moves(moves: &str) -> Cube {
-    Cube::solved().apply_all(Move::parse_sequence(moves).unwrap())
+    Cube::solved().apply_str(moves).unwrap()
+}
+
+/// Solvable cubes, scrambled from solved. Arbitrary stickers would almost never be a real cube.
+impl quickcheck::Arbitrary for Cube {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        Cube::solved().apply_all(Vec::<Move>::arbitrary(g))
+    }
+}
+
+/// Shrinks toward moves earlier in `Move::all`, lower faces and then Single before Double before
+/// Reverse, so failing sequences reduce to the simplest moves that still fail.
+impl quickcheck::Arbitrary for Move {
+    fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
+        use rand::seq::IteratorRandom;
+
+        Move::all().choose(g).unwrap()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let this = *self;
+        Box::new(Move::all().take_while(move |&m| m != this))
+    }
+}
+
+thread_local! {
+    /// What `captured_logs` has collected on this thread, while it runs.
+    static CAPTURED: core::cell::RefCell<Option<Vec<(log::Level, String)>>> =
+        const { core::cell::RefCell::new(None) };
+}
+
+struct CapturingLogger;
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        CAPTURED.with(|c| c.borrow().is_some())
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        CAPTURED.with(|c| {
+            if let Some(logs) = &mut *c.borrow_mut() {
+                logs.push((record.level(), message));
+            }
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Every message logged on this thread while `f` runs, with its level. Other threads' messages,
+/// like those of a threaded `Solver::solve`, aren't included.
+pub fn captured_logs(f: impl FnOnce()) -> Vec<(log::Level, String)> {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&CapturingLogger).expect("Nothing else sets a logger in tests");
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+
+    CAPTURED.with(|c| *c.borrow_mut() = Some(Vec::new()));
+    f();
+    CAPTURED.with(|c| c.borrow_mut().take().unwrap())
 }